@@ -1,6 +1,6 @@
 use knope_versioning::{
     changes::ChangeType,
-    release_notes::{CommitFooter, CustomChangeType, SectionName, Sections},
+    release_notes::{BumpLevel, CommitFooter, CustomChangeType, SectionName, Sections},
 };
 use serde::{Deserialize, Serialize};
 
@@ -12,15 +12,20 @@ pub struct ChangelogSection {
     pub(crate) footers: Vec<CommitFooter>,
     #[serde(default)]
     pub(crate) types: Vec<CustomChangeType>,
+    /// How much changes in this section bump the version, defaults to `patch`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) bump: Option<BumpLevel>,
 }
 
 pub fn convert_to_versioning(changelog_sections: Vec<ChangelogSection>) -> Sections {
     let mut defaults = Sections::defaults();
     let mut sections = Vec::with_capacity(changelog_sections.len());
+    let mut bumps = Vec::new();
     for ChangelogSection {
         name,
         footers,
         types,
+        bump,
     } in changelog_sections
     {
         let mut sources: Vec<ChangeType> = footers
@@ -29,6 +34,12 @@ pub fn convert_to_versioning(changelog_sections: Vec<ChangelogSection>) -> Secti
             .chain(types.into_iter().map(ChangeType::from))
             .collect();
         defaults.retain(|(_, source)| !sources.contains(source));
+        if let Some(bump) = bump {
+            bumps.extend(sources.iter().filter_map(|source| match source {
+                ChangeType::Custom(custom) => Some((custom.clone(), bump)),
+                _ => None,
+            }));
+        }
 
         // If there's a duplicate section name, combine it
         while let Some((index, (_, change_type))) = defaults
@@ -44,5 +55,8 @@ pub fn convert_to_versioning(changelog_sections: Vec<ChangelogSection>) -> Secti
     let defaults = defaults
         .into_iter()
         .map(|(name, source)| (name, vec![source]));
-    Sections(defaults.into_iter().chain(sections).collect())
+    Sections {
+        sections: defaults.into_iter().chain(sections).collect(),
+        bumps,
+    }
 }
@@ -2,4 +2,4 @@ pub mod changelog_section;
 mod package;
 
 pub use changelog_section::ChangelogSection;
-pub use package::{Asset, AssetNameError, Assets, Package, VersionedFile};
+pub use package::{Asset, AssetNameError, Assets, Package, ScopesAndPaths, VersionedFile};
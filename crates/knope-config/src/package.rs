@@ -1,6 +1,10 @@
 use std::ops::Not;
 
-use knope_versioning::{UnknownFile, VersionedFileConfig};
+use knope_versioning::{
+    release_notes::CommitLinks,
+    semver::{Channels, Pep440},
+    UnknownFile, VersionedFileConfig,
+};
 use relative_path::RelativePathBuf;
 use serde::{Deserialize, Serialize};
 use toml::Spanned;
@@ -17,13 +21,61 @@ pub struct Package {
     pub changelog: Option<RelativePathBuf>,
     /// Optional scopes that can be used to filter commits when running [`Step::PrepareRelease`].
     pub scopes: Option<Vec<String>>,
+    /// Optional globs of files that a commit must touch to count toward this package.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<String>>,
+    /// How to combine `scopes` and `paths` when both are set.
+    #[serde(default, skip_serializing_if = "ScopesAndPaths::is_default")]
+    pub scopes_and_paths: ScopesAndPaths,
+    /// Regular expressions matching commit messages which should be ignored entirely.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_commits: Vec<String>,
     /// Extra sections that should be added to the changelog from custom footers in commit messages.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub extra_changelog_sections: Vec<ChangelogSection>,
+    /// Link changes from commits to their hash, author, and pull request in release notes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_links: Option<CommitLinks>,
+    /// Thank the authors of changes in a "Contributors" section of release notes
+    #[serde(default, skip_serializing_if = "<&bool>::not")]
+    pub contributors: bool,
+    /// A template to render new releases in the changelog with, instead of the default layout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changelog_template: Option<RelativePathBuf>,
+    /// A template to render the body of forge releases with, if not the changelog template
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_template: Option<RelativePathBuf>,
     /// The assets, if any, to upload with each release
     pub assets: Option<Assets>,
     #[serde(default, skip_serializing_if = "<&bool>::not")]
     pub ignore_go_major_versioning: bool,
+    /// Read and write versions of Python files in PEP 440 form
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pep440: Option<Pep440>,
+    /// The order of pre-release labels, from least to most stable
+    #[serde(default, skip_serializing_if = "Channels::is_empty")]
+    pub prerelease_channels: Channels,
+    /// Where the change files for this package are, instead of the project-wide directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changeset_directory: Option<RelativePathBuf>,
+}
+
+/// How `scopes` and `paths` combine to decide whether a commit belongs to a package.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScopesAndPaths {
+    /// A commit must touch the paths _and_ match the scopes.
+    #[default]
+    All,
+    /// A commit must touch the paths _or_ have one of the scopes.
+    Any,
+}
+
+impl ScopesAndPaths {
+    #[allow(clippy::trivially_copy_pass_by_ref)] // Required signature for serde
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
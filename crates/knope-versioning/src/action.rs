@@ -34,10 +34,15 @@ impl ReleaseTag {
 
     #[must_use]
     pub fn is_release_tag(val: &str, package_name: &package::Name) -> bool {
+        Self::version(val, package_name).is_some()
+    }
+
+    /// The version that a tag is for, if it's a release tag of a particular package
+    #[must_use]
+    pub fn version(val: &str, package_name: &package::Name) -> Option<Version> {
         let tag_prefix = Self::tag_prefix(package_name);
         val.strip_prefix(&tag_prefix)
             .and_then(|version_str| Version::from_str(version_str).ok())
-            .is_some()
     }
 
     /// The prefix for tags for a particular package
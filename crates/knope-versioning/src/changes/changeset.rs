@@ -1 +1,437 @@
+use std::{collections::HashMap, ops::Range};
+
+use changesets::{ChangeSet, Release};
+use relative_path::{RelativePath, RelativePathBuf};
+
+use super::{conventional_commit::Author, IssueReference};
+
+/// Where change files are, unless configured otherwise.
 pub const CHANGESET_DIR: &str = ".changeset";
+
+/// Keys in the front matter of a change file which hold [`Metadata`] instead of naming a package.
+pub const METADATA_KEYS: &[&str] = &["issues", "pr", "authors"];
+
+/// Details about a change from the front matter of its change file, like:
+///
+/// ```markdown
+/// ---
+/// my-package: minor
+/// issues: [123, ABC-42]
+/// pr: 456
+/// authors: [alice, "Bob <bob@example.com>"]
+/// ---
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Metadata {
+    pub issues: Vec<IssueReference>,
+    pub pull_request: Option<u64>,
+    pub authors: Vec<Author>,
+}
+
+impl Metadata {
+    /// Whether a line of front matter starting with `key` is metadata, not a package.
+    #[must_use]
+    pub fn is_key(key: &str) -> bool {
+        METADATA_KEYS.contains(&key.trim())
+    }
+
+    /// Add the `value` of a line of front matter starting with `key`.
+    ///
+    /// # Errors
+    ///
+    /// If `key` isn't one of [`METADATA_KEYS`] or `value` isn't valid for it.
+    pub fn add(&mut self, key: &str, value: &str) -> Result<(), MetadataError> {
+        match key.trim() {
+            "issues" => {
+                for item in list(value) {
+                    let issue = item
+                        .parse()
+                        .ok()
+                        .map(IssueReference::Number)
+                        .or_else(|| IssueReference::parse(item))
+                        .ok_or_else(|| MetadataError::InvalidIssue(item.to_string()))?;
+                    if !self.issues.contains(&issue) {
+                        self.issues.push(issue);
+                    }
+                }
+            }
+            "pr" => {
+                let value = value.trim();
+                let number = value
+                    .trim_start_matches('#')
+                    .parse()
+                    .map_err(|_| MetadataError::InvalidPullRequest(value.to_string()))?;
+                self.pull_request = Some(number);
+            }
+            "authors" => {
+                for item in list(value) {
+                    let author = Author::parse(item)
+                        .or_else(|| {
+                            let username = item.trim_start_matches('@');
+                            (!username.is_empty() && !username.contains(char::is_whitespace)).then(
+                                || Author {
+                                    name: username.to_string(),
+                                    email: String::new(),
+                                    login: Some(username.to_string()),
+                                },
+                            )
+                        })
+                        .ok_or_else(|| MetadataError::InvalidAuthor(item.to_string()))?;
+                    if !self.authors.contains(&author) {
+                        self.authors.push(author);
+                    }
+                }
+            }
+            other => return Err(MetadataError::UnknownKey(other.to_string())),
+        }
+        Ok(())
+    }
+}
+
+/// The items of a value like `[123, "ABC-42"]`, or just `123`.
+fn list(value: &str) -> impl Iterator<Item = &str> {
+    let value = value.trim();
+    value
+        .strip_prefix('[')
+        .and_then(|inner| inner.strip_suffix(']'))
+        .unwrap_or(value)
+        .split(',')
+        .map(|item| item.trim().trim_matches(['"', '\'']).trim())
+        .filter(|item| !item.is_empty())
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum MetadataError {
+    #[error("`{0}` is not an issue, expected a number like `123` or a Jira key like `ABC-42`")]
+    InvalidIssue(String),
+    #[error("`{0}` is not a pull request number, expected something like `456`")]
+    InvalidPullRequest(String),
+    #[error("`{0}` is not an author, expected a username like `alice` or `Name <email>`")]
+    InvalidAuthor(String),
+    #[error("`{0}` is not a kind of metadata")]
+    UnknownKey(String),
+}
+
+/// Parse a change file, collecting every problem with its front matter instead of stopping at
+/// the first.
+///
+/// The metadata lines of the front matter are kept separate from the packages.
+/// `file_name` is expected to end in `.md`, the rest of it identifies the change.
+#[must_use]
+pub fn parse_change_file(file_name: &str, contents: &str) -> ChangeFile {
+    let mut file = ChangeFile {
+        packages: Vec::new(),
+        metadata: Metadata::default(),
+        problems: Vec::new(),
+        unique_id: file_name.strip_suffix(".md").unwrap_or(file_name).into(),
+        summary: String::new(),
+    };
+    let mut lines = contents.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line.trim_end_matches(['\n', '\r'])))
+    });
+    let Some((_, opening)) = lines.next().filter(|(_, line)| line.trim() == "---") else {
+        let first_line = contents.lines().next().unwrap_or_default();
+        file.problem(ChangeFileError::MissingFrontMatter, 0..first_line.len());
+        return file;
+    };
+
+    let mut closed = false;
+    for (start, line) in lines.by_ref() {
+        if line.trim() == "---" {
+            closed = true;
+            break;
+        }
+        file.parse_line(start, line);
+    }
+    if !closed {
+        file.packages.clear();
+        file.problems.clear();
+        file.problem(ChangeFileError::UnclosedFrontMatter, 0..opening.len());
+        return file;
+    }
+    if file.packages.is_empty() && file.problems.is_empty() {
+        file.problem(ChangeFileError::NoPackages, 0..opening.len());
+    }
+
+    file.summary = lines
+        .map(|(_, line)| line)
+        .skip_while(|line| line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    file
+}
+
+/// A change file split into its parts, along with every problem in its front matter.
+#[derive(Clone, Debug)]
+pub struct ChangeFile {
+    /// Each package the change is for, in the order they're listed
+    pub packages: Vec<ListedPackage>,
+    pub metadata: Metadata,
+    /// Every problem with the front matter, in the order they appear
+    pub problems: Vec<ChangeFileProblem>,
+    unique_id: changesets::UniqueId,
+    summary: String,
+}
+
+impl ChangeFile {
+    /// The parsed change, for use with [`ChangeFiles::add_directory`].
+    ///
+    /// # Errors
+    ///
+    /// If there were any [`ChangeFile::problems`].
+    pub fn into_change(self) -> Result<(changesets::Change, Metadata), Vec<ChangeFileProblem>> {
+        if !self.problems.is_empty() {
+            return Err(self.problems);
+        }
+        let change = changesets::Change {
+            unique_id: self.unique_id,
+            versioning: self
+                .packages
+                .into_iter()
+                .map(|package| (package.name, package.change_type))
+                .collect(),
+            summary: self.summary,
+        };
+        Ok((change, self.metadata))
+    }
+
+    fn parse_line(&mut self, start: usize, line: &str) {
+        let Some((key, value)) = line.split_once(':') else {
+            self.problem(ChangeFileError::InvalidLine, start..start + line.len());
+            return;
+        };
+        let name = key.trim();
+        let name_start = start + leading_whitespace(key);
+        let name_span = name_start..name_start + name.len();
+        let change_type = value.trim();
+        let change_type_start = start + key.len() + 1 + leading_whitespace(value);
+        let change_type_span = change_type_start..change_type_start + change_type.len();
+
+        if Metadata::is_key(name) {
+            if let Err(err) = self.metadata.add(name, value) {
+                self.problem(err.into(), change_type_span);
+            }
+        } else if self.packages.iter().any(|package| package.name == name) {
+            self.problem(
+                ChangeFileError::DuplicatePackage(name.to_string()),
+                name_span,
+            );
+        } else {
+            self.packages.push(ListedPackage {
+                name: name.to_string(),
+                change_type: changesets::ChangeType::from(change_type),
+                name_span,
+                change_type_span,
+            });
+        }
+    }
+
+    fn problem(&mut self, error: ChangeFileError, span: Range<usize>) {
+        self.problems.push(ChangeFileProblem { error, span });
+    }
+}
+
+/// How much whitespace `text` starts with.
+fn leading_whitespace(text: &str) -> usize {
+    text.len() - text.trim_start().len()
+}
+
+/// A `package: change type` line in the front matter of a change file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListedPackage {
+    pub name: String,
+    pub change_type: changesets::ChangeType,
+    /// Where `name` is in the change file, in bytes
+    pub name_span: Range<usize>,
+    /// Where `change_type` is in the change file, in bytes
+    pub change_type_span: Range<usize>,
+}
+
+/// Something wrong with a change file, and where it is (in bytes).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChangeFileProblem {
+    pub error: ChangeFileError,
+    pub span: Range<usize>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum ChangeFileError {
+    #[error("Expected front matter starting with `---`")]
+    MissingFrontMatter,
+    #[error("This front matter is never closed with `---`")]
+    UnclosedFrontMatter,
+    #[error("Expected a line like `package: change type`")]
+    InvalidLine,
+    #[error("`{0}` is listed more than once")]
+    DuplicatePackage(String),
+    #[error("No packages are listed")]
+    NoPackages,
+    #[error(transparent)]
+    Metadata(#[from] MetadataError),
+}
+
+/// Every change file grouped by package, along with the metadata of each.
+///
+/// Change files can be in several directories, each is kept separate so that files with the same
+/// name don't collide.
+#[derive(Clone, Debug, Default)]
+pub struct ChangeFiles {
+    pub(crate) directories: Vec<ChangeFileDirectory>,
+}
+
+impl ChangeFiles {
+    /// Add the change files from `directory`, each from [`ChangeFile::into_change`].
+    pub fn add_directory(
+        &mut self,
+        directory: RelativePathBuf,
+        change_files: impl IntoIterator<Item = (changesets::Change, Metadata)>,
+    ) {
+        let mut metadata = HashMap::new();
+        let changeset = change_files
+            .into_iter()
+            .map(|(change, change_metadata)| {
+                metadata.insert(change.unique_id.to_file_name(), change_metadata);
+                change
+            })
+            .collect::<ChangeSet>();
+        self.directories.push(ChangeFileDirectory {
+            path: directory,
+            releases: changeset.into(),
+            metadata,
+        });
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ChangeFileDirectory {
+    pub(crate) path: RelativePathBuf,
+    pub(crate) releases: Vec<Release>,
+    /// The metadata of each change file, by file name
+    pub(crate) metadata: HashMap<String, Metadata>,
+}
+
+impl ChangeFileDirectory {
+    pub(crate) fn file_path(&self, id: &changesets::UniqueId) -> RelativePathBuf {
+        RelativePath::new(&self.path).join(id.to_file_name())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn parse_metadata() {
+        let (change, metadata) = parse_change_file(
+            "a_change.md",
+            "---\nknope: minor\nissues: [123, \"ABC-42\", #124]\npr: 456\nauthors: [alice, \"Bob <bob@example.com>\"]\n---\n\n# A change\n",
+        )
+        .into_change()
+        .unwrap();
+        assert_eq!(change.versioning.len(), 1);
+        assert_eq!(change.summary, "# A change");
+        assert_eq!(
+            metadata,
+            Metadata {
+                issues: vec![
+                    IssueReference::Number(123),
+                    IssueReference::Jira(String::from("ABC-42")),
+                    IssueReference::Number(124),
+                ],
+                pull_request: Some(456),
+                authors: vec![
+                    Author {
+                        name: String::from("alice"),
+                        email: String::new(),
+                        login: Some(String::from("alice")),
+                    },
+                    Author {
+                        name: String::from("Bob"),
+                        email: String::from("bob@example.com"),
+                        login: None,
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn metadata_only_in_front_matter() {
+        let (change, metadata) = parse_change_file(
+            "a_change.md",
+            "---\nknope: patch\n---\n\n# A change\n\npr: 1\n",
+        )
+        .into_change()
+        .unwrap();
+        assert_eq!(metadata, Metadata::default());
+        assert_eq!(change.summary, "# A change\n\npr: 1");
+    }
+
+    fn problems(contents: &str) -> Vec<(ChangeFileError, Range<usize>)> {
+        parse_change_file("a_change.md", contents)
+            .problems
+            .into_iter()
+            .map(|problem| (problem.error, problem.span))
+            .collect()
+    }
+
+    #[test]
+    fn every_problem_is_located() {
+        assert_eq!(
+            problems("---\nknope: minor\npr: soon\n knope : major\nnope\n---\n# A change\n"),
+            vec![
+                (
+                    ChangeFileError::Metadata(MetadataError::InvalidPullRequest(String::from(
+                        "soon"
+                    ))),
+                    21..25
+                ),
+                (
+                    ChangeFileError::DuplicatePackage(String::from("knope")),
+                    27..32
+                ),
+                (ChangeFileError::InvalidLine, 41..45),
+            ]
+        );
+    }
+
+    #[test]
+    fn broken_front_matter() {
+        assert_eq!(
+            problems("# A change\n"),
+            vec![(ChangeFileError::MissingFrontMatter, 0..10)]
+        );
+        assert_eq!(
+            problems("---\nknope: minor\nnope\n# A change\n"),
+            vec![(ChangeFileError::UnclosedFrontMatter, 0..3)]
+        );
+        assert_eq!(
+            problems("---\npr: 3\n---\n# A change\n"),
+            vec![(ChangeFileError::NoPackages, 0..3)]
+        );
+    }
+
+    #[test]
+    fn invalid_metadata() {
+        let mut metadata = Metadata::default();
+        assert_eq!(
+            metadata.add("issues", "[12, twelve]"),
+            Err(MetadataError::InvalidIssue(String::from("twelve")))
+        );
+        assert_eq!(
+            metadata.add("pr", "soon"),
+            Err(MetadataError::InvalidPullRequest(String::from("soon")))
+        );
+        assert_eq!(
+            metadata.add("authors", "Some One"),
+            Err(MetadataError::InvalidAuthor(String::from("Some One")))
+        );
+    }
+}
@@ -1,8 +1,192 @@
-use git_conventional::{Commit, Footer, Type};
+use std::{fmt, fmt::Display};
+
+use git_conventional::{Commit as ParsedCommit, Footer, Type};
+use regex::Regex;
 use tracing::debug;
 
-use super::{Change, ChangeSource, ChangeType};
-use crate::release_notes::Sections;
+use super::{
+    issues::{self, parse_issue_references, IssueReference},
+    Change, ChangeSource, ChangeType,
+};
+use crate::release_notes::{SectionSource, Sections};
+
+/// A commit to look for conventional commit changes in.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Commit {
+    pub message: String,
+    /// The full hash of the commit, if known
+    pub hash: Option<String>,
+    pub author: Option<Author>,
+}
+
+impl From<&str> for Commit {
+    fn from(message: &str) -> Self {
+        Self {
+            message: message.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Who wrote a commit.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Author {
+    pub name: String,
+    pub email: String,
+    /// The username of the author on the forge, if it was looked up
+    pub login: Option<String>,
+}
+
+impl Author {
+    /// Parse an author from a `Name <email>` trailer value, like in `Co-authored-by`.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        let (name, email) = value.trim().strip_suffix('>')?.rsplit_once('<')?;
+        let (name, email) = (name.trim(), email.trim());
+        if email.is_empty() {
+            return None;
+        }
+        Some(Self {
+            name: name.to_string(),
+            email: email.to_string(),
+            login: None,
+        })
+    }
+
+    /// The username of the author, either looked up from the forge or from a GitHub `noreply` email.
+    #[must_use]
+    pub fn username(&self) -> Option<&str> {
+        if let Some(login) = &self.login {
+            return Some(login);
+        }
+        let local = self.email.strip_suffix("@users.noreply.github.com")?;
+        Some(
+            local
+                .split_once('+')
+                .map_or(local, |(_, username)| username),
+        )
+    }
+}
+
+/// Where a change from a conventional commit came from.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CommitSource {
+    /// The summary of the commit, followed by the footer the change came from (if any)
+    pub summary: String,
+    /// The full hash of the commit, if known
+    pub hash: Option<String>,
+    pub author: Option<Author>,
+    /// Everyone credited with a `Co-authored-by` trailer
+    pub co_authors: Vec<Author>,
+    /// The number from a `(#123)` at the end of the commit summary, as added by squash merges
+    pub pull_request: Option<u64>,
+}
+
+impl From<&str> for CommitSource {
+    fn from(summary: &str) -> Self {
+        Self {
+            summary: summary.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Display for CommitSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.summary)
+    }
+}
+
+/// Decides which conventional commits apply to a package.
+#[derive(Clone, Debug, Default)]
+pub struct CommitFilter {
+    /// If set, commits with a scope only apply if it's one of these.
+    pub scopes: Option<Vec<String>>,
+    /// Commits with messages matching any of these are ignored entirely.
+    pub ignore: Vec<Regex>,
+}
+
+/// A trailer which excludes a commit from release notes (while still bumping the version).
+const SKIP_TRAILER: &str = "Changelog";
+/// A trailer which replaces the description of a commit in release notes.
+const DESCRIPTION_TRAILER: &str = "Changelog-Description";
+/// A trailer crediting another author of a commit.
+const CO_AUTHOR_TRAILER: &str = "Co-authored-by";
+
+/// Standard Git trailers, which are allowed in commits even though Knope doesn't use them.
+const GIT_TRAILERS: &[&str] = &[
+    "Signed-off-by",
+    "Acked-by",
+    "Reviewed-by",
+    "Tested-by",
+    "Reported-by",
+    "Suggested-by",
+    "Helped-by",
+];
+
+/// A part of a commit message which Knope doesn't understand, found by [`check_commit`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CommitProblem {
+    /// The message isn't a conventional commit, for this reason
+    NotConventional(String),
+    /// The commit has a scope which no package is configured with
+    UnknownScope(String),
+    /// A footer which doesn't do anything
+    UnknownFooter(String),
+}
+
+impl Display for CommitProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConventional(reason) => write!(f, "not a conventional commit: {reason}"),
+            Self::UnknownScope(scope) => write!(f, "unknown scope `{scope}`"),
+            Self::UnknownFooter(token) => write!(f, "unknown footer `{token}`"),
+        }
+    }
+}
+
+/// Check that `message` is a conventional commit which Knope fully understands.
+///
+/// If `scopes` is set, any scope of the commit must be one of them.
+/// Each footer must be a breaking change, be used by one of `sections`, or be one of Knope's
+/// trailers (like `Changelog: skip`), an issue reference (like `Closes: #12`), or a standard Git
+/// trailer (like `Signed-off-by`).
+#[must_use]
+pub fn check_commit(
+    message: &str,
+    scopes: Option<&[String]>,
+    sections: &[&Sections],
+) -> Vec<CommitProblem> {
+    let commit = match ParsedCommit::parse(message.trim()) {
+        Ok(commit) => commit,
+        Err(err) => return vec![CommitProblem::NotConventional(err.to_string())],
+    };
+    let mut problems = Vec::new();
+    if let (Some(scope), Some(scopes)) = (commit.scope(), scopes) {
+        if !scopes
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(scope.as_str()))
+        {
+            problems.push(CommitProblem::UnknownScope(scope.to_string()));
+        }
+    }
+    for footer in commit.footers() {
+        let token = footer.token().as_str();
+        let known = footer.breaking()
+            || sections
+                .iter()
+                .any(|sections| sections.contains_footer(footer))
+            || [SKIP_TRAILER, DESCRIPTION_TRAILER, CO_AUTHOR_TRAILER]
+                .iter()
+                .chain(GIT_TRAILERS)
+                .any(|known| known.eq_ignore_ascii_case(token))
+            || issues::is_keyword(token);
+        if !known {
+            problems.push(CommitProblem::UnknownFooter(token.to_string()));
+        }
+    }
+    problems
+}
 
 /// Try to parse each commit message as a [conventional commit](https://www.conventionalcommits.org/).
 ///
@@ -11,32 +195,136 @@ use crate::release_notes::Sections;
 /// 1. If the commit message doesn't follow the conventional commit format, it is ignored.
 /// 2. For non-standard change types, only those included will be considered.
 /// 3. For non-standard footers, only those included will be considered.
-pub(crate) fn changes_from_commit_messages<'a, Message: AsRef<str>>(
-    commit_messages: &'a [Message],
-    scopes: Option<&'a Vec<String>>,
+/// 4. Commits matching any of `filter.ignore` are ignored.
+/// 5. Commits with a scope not in `filter.scopes` (if set) are ignored.
+///
+/// # Trailers
+///
+/// A `Changelog: skip` trailer leaves all changes from a commit out of release notes,
+/// and `Changelog-Description: <text>` replaces the description of the commit's summary.
+/// Each `Co-authored-by: Name <email>` trailer is recorded as a co-author of the changes.
+///
+/// # Squash commits
+///
+/// If `split_squash_commits` is set, any line of a message starting with `* ` followed by a
+/// conventional commit summary (as GitHub formats squash merges) begins a new commit.
+/// The lines after it (including footers) belong to that commit, until the next one.
+/// The squash title itself is then not a change, but its pull request (like `(#12)`) is used for
+/// each of the split commits.
+pub(crate) fn changes_from_commits<'a>(
+    commits: &'a [Commit],
+    filter: &'a CommitFilter,
     changelog_sections: &'a Sections,
+    split_squash_commits: bool,
 ) -> impl Iterator<Item = Change> + 'a {
-    if let Some(scopes) = scopes {
+    if let Some(scopes) = &filter.scopes {
         debug!("Only checking commits with scopes: {scopes:?}");
     }
-    commit_messages.iter().flat_map(move |message| {
-        changes_from_commit_message(message.as_ref(), scopes, changelog_sections).into_iter()
-    })
+    commits
+        .iter()
+        .flat_map(move |commit| {
+            let split = split_squash_commits
+                .then(|| split_squash_commit(&commit.message))
+                .flatten();
+            let Some((messages, pull_request)) = split else {
+                return vec![(commit.clone(), None)];
+            };
+            messages
+                .into_iter()
+                .map(|message| {
+                    let commit = Commit {
+                        message,
+                        hash: commit.hash.clone(),
+                        author: commit.author.clone(),
+                    };
+                    (commit, pull_request)
+                })
+                .collect()
+        })
+        .flat_map(move |(commit, pull_request)| {
+            changes_from_commit(&commit, filter, changelog_sections, pull_request).into_iter()
+        })
 }
 
-fn changes_from_commit_message(
-    commit_message: &str,
-    scopes: Option<&Vec<String>>,
+/// Split a squash-merged commit message into the messages of the commits it contains, along with
+/// the pull request from its title.
+///
+/// Returns `None` if there is nothing to split.
+fn split_squash_commit(message: &str) -> Option<(Vec<String>, Option<u64>)> {
+    let pull_request = message.lines().next().and_then(pull_request_number);
+    // The first message is the title of the squash commit, and anything before the first split
+    let mut messages = vec![String::new()];
+    for line in message.lines() {
+        let squashed_summary = line
+            .strip_prefix("* ")
+            .filter(|summary| ParsedCommit::parse(summary.trim()).is_ok());
+        if let Some(summary) = squashed_summary {
+            messages.push(summary.to_string());
+        } else if let Some(current) = messages.last_mut() {
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(line);
+        }
+    }
+    let split = messages.split_off(1);
+    (!split.is_empty()).then_some((split, pull_request))
+}
+
+/// The changes from one commit, using `pull_request` if the commit doesn't name its own.
+/// The issues a commit refers to, skipping the `type(scope)!:` prefix so that `fix: #12` isn't
+/// read as fixing #12.
+fn issue_references(message: &str, commit: &ParsedCommit) -> Vec<IssueReference> {
+    let rest = message.trim().split_once('\n').map_or("", |(_, rest)| rest);
+    parse_issue_references(&format!("{}\n{rest}", commit.description()))
+}
+
+fn changes_from_commit(
+    commit: &Commit,
+    filter: &CommitFilter,
     changelog_sections: &Sections,
+    pull_request: Option<u64>,
 ) -> Vec<Change> {
-    let Some(commit) = Commit::parse(commit_message.trim()).ok() else {
+    let Commit {
+        message: commit_message,
+        hash,
+        author,
+    } = commit;
+    if let Some(pattern) = filter
+        .ignore
+        .iter()
+        .find(|pattern| pattern.is_match(commit_message))
+    {
+        debug!("Ignoring commit matching {pattern}: {commit_message}");
+        return Vec::new();
+    }
+    let Some(commit) = ParsedCommit::parse(commit_message.trim()).ok() else {
         return Vec::new();
     };
+    let co_authors = commit
+        .footers()
+        .iter()
+        .filter(|footer| {
+            footer
+                .token()
+                .as_str()
+                .eq_ignore_ascii_case(CO_AUTHOR_TRAILER)
+        })
+        .filter_map(|footer| Author::parse(footer.value()))
+        .collect::<Vec<_>>();
+    let source = |summary: String| CommitSource {
+        summary,
+        hash: hash.clone(),
+        author: author.clone(),
+        co_authors: co_authors.clone(),
+        pull_request: pull_request_number(commit.description()).or(pull_request),
+    };
+    let issues = issue_references(commit_message, &commit);
     let mut has_breaking_footer = false;
     let commit_summary = format_commit_summary(&commit);
 
     if let Some(commit_scope) = commit.scope() {
-        if let Some(scopes) = scopes {
+        if let Some(scopes) = &filter.scopes {
             if !scopes
                 .iter()
                 .any(|s| s.eq_ignore_ascii_case(commit_scope.as_str()))
@@ -46,6 +334,18 @@ fn changes_from_commit_message(
         }
     }
 
+    let skip_release_notes = commit.footers().iter().any(|footer| {
+        footer.token().as_str().eq_ignore_ascii_case(SKIP_TRAILER)
+            && footer.value().trim().eq_ignore_ascii_case("skip")
+    });
+    let description_override = commit.footers().iter().find_map(|footer| {
+        footer
+            .token()
+            .as_str()
+            .eq_ignore_ascii_case(DESCRIPTION_TRAILER)
+            .then(|| footer.value().trim())
+    });
+
     let mut changes = Vec::new();
     for footer in commit.footers() {
         if footer.breaking() {
@@ -56,10 +356,12 @@ fn changes_from_commit_message(
         changes.push(Change {
             change_type: footer.token().into(),
             description: footer.value().into(),
-            original_source: ChangeSource::ConventionalCommit(format_commit_footer(
+            original_source: ChangeSource::ConventionalCommit(source(format_commit_footer(
                 &commit_summary,
                 footer,
-            )),
+            ))),
+            skip_release_notes,
+            issues: issues.clone(),
         });
     }
 
@@ -69,20 +371,32 @@ fn changes_from_commit_message(
         ChangeType::Feature
     } else if commit.type_() == Type::FIX {
         ChangeType::Fix
+    } else if let Some(custom) = changelog_sections.custom_commit_type(commit.type_().as_str()) {
+        ChangeType::Custom(SectionSource::CustomChangeType(custom.clone()))
     } else {
         return changes; // The commit description isn't a change itself, only (maybe) footers were.
     };
 
     changes.push(Change {
         change_type: commit_description_change_type,
-        description: commit.description().into(),
-        original_source: ChangeSource::ConventionalCommit(commit_summary),
+        description: description_override
+            .unwrap_or_else(|| commit.description())
+            .into(),
+        original_source: ChangeSource::ConventionalCommit(source(commit_summary)),
+        skip_release_notes,
+        issues,
     });
 
     changes
 }
 
-fn format_commit_summary(commit: &Commit) -> String {
+/// Find a pull request number at the end of a commit summary, like `Fix a bug (#123)`.
+fn pull_request_number(summary: &str) -> Option<u64> {
+    let (_, number) = summary.trim_end().strip_suffix(')')?.rsplit_once("(#")?;
+    number.parse().ok()
+}
+
+fn format_commit_summary(commit: &ParsedCommit) -> String {
     let commit_scope = commit
         .scope()
         .map(|s| s.to_string())
@@ -122,9 +436,52 @@ mod tests {
     use super::*;
     use crate::{
         changes::ChangeSource,
-        release_notes::{SectionSource, Sections},
+        release_notes::{CommitFooter, SectionSource, Sections},
     };
 
+    #[test]
+    fn check_valid_commit() {
+        let scopes = [String::from("knope")];
+        let sections = Sections::default();
+        assert_eq!(
+            check_commit(
+                "feat(knope): A feature\n\nChangelog-Note: A note\nCloses: #12\nSigned-off-by: Me <me@example.com>\nBREAKING CHANGE: Something broke",
+                Some(&scopes),
+                &[&sections],
+            ),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn check_invalid_commits() {
+        let scopes = [String::from("knope")];
+        let sections = Sections::default();
+        assert_eq!(
+            check_commit("Not conventional", Some(&scopes), &[&sections]),
+            vec![CommitProblem::NotConventional(
+                git_conventional::Commit::parse("Not conventional")
+                    .unwrap_err()
+                    .to_string()
+            )]
+        );
+        assert_eq!(
+            check_commit(
+                "fix(other): A fix\n\nMystery-Footer: what is this",
+                Some(&scopes),
+                &[&sections],
+            ),
+            vec![
+                CommitProblem::UnknownScope(String::from("other")),
+                CommitProblem::UnknownFooter(String::from("Mystery-Footer")),
+            ]
+        );
+        assert_eq!(
+            check_commit("fix(other): A fix", None, &[&sections]),
+            Vec::new()
+        );
+    }
+
     #[test]
     fn commit_types() {
         let commits = &[
@@ -133,36 +490,51 @@ mod tests {
             "feat!: add a feature",
             "feat: add another feature",
         ];
-        let changes =
-            changes_from_commit_messages(commits, None, &Sections::default()).collect_vec();
+        let changes = changes_from_commits(
+            &commits.map(Commit::from),
+            &CommitFilter::default(),
+            &Sections::default(),
+            false,
+        )
+        .collect_vec();
         assert_eq!(
             changes,
             vec![
                 Change {
                     change_type: ChangeType::Fix,
                     description: "a bug".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from("fix: a bug")),
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from(
+                        "fix: a bug"
+                    )),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
                 },
                 Change {
                     change_type: ChangeType::Breaking,
                     description: "a breaking bug fix".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from(
                         "fix!: a breaking bug fix"
                     )),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
                 },
                 Change {
                     change_type: ChangeType::Breaking,
                     description: "add a feature".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from(
                         "feat!: add a feature"
                     )),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
                 },
                 Change {
                     change_type: ChangeType::Feature,
                     description: "add another feature".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from(
                         "feat: add another feature"
                     )),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
                 }
             ]
         );
@@ -174,30 +546,43 @@ mod tests {
             "fix: a bug\n\nBREAKING CHANGE: something broke",
             "feat: a features\n\nBREAKING CHANGE: something else broke",
         ];
-        let changes =
-            changes_from_commit_messages(&commits, None, &Sections::default()).collect_vec();
+        let changes = changes_from_commits(
+            &commits.map(Commit::from),
+            &CommitFilter::default(),
+            &Sections::default(),
+            false,
+        )
+        .collect_vec();
         assert_eq!(
             changes,
             vec![
                 Change {
                     change_type: ChangeType::Breaking,
                     description: "something broke".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from("fix: a bug\n\tContaining footer BREAKING CHANGE: something broke")),
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from("fix: a bug\n\tContaining footer BREAKING CHANGE: something broke")),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
                 },
                 Change {
                     change_type: ChangeType::Fix,
                     description: "a bug".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from("fix: a bug")),
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from("fix: a bug")),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
                 },
                 Change {
                     change_type: ChangeType::Breaking,
                     description: "something else broke".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from("feat: a features\n\tContaining footer BREAKING CHANGE: something else broke")),
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from("feat: a features\n\tContaining footer BREAKING CHANGE: something else broke")),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
                 },
                 Change {
                     change_type: ChangeType::Feature,
                     description: "a features".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from("feat: a features")),
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from("feat: a features")),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
                 },
             ]
         );
@@ -209,24 +594,33 @@ mod tests {
             "feat(scope)!: Wrong scope breaking change!",
             "fix: No scope",
         ];
-        let changes =
-            changes_from_commit_messages(&commits, None, &Sections::default()).collect_vec();
+        let changes = changes_from_commits(
+            &commits.map(Commit::from),
+            &CommitFilter::default(),
+            &Sections::default(),
+            false,
+        )
+        .collect_vec();
         assert_eq!(
             changes,
             vec![
                 Change {
                     change_type: ChangeType::Breaking,
                     description: "Wrong scope breaking change!".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from(
                         "feat(scope)!: Wrong scope breaking change!"
                     )),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
                 },
                 Change {
                     change_type: ChangeType::Fix,
                     description: "No scope".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from(
                         "fix: No scope"
                     )),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
                 }
             ]
         );
@@ -240,10 +634,14 @@ mod tests {
             "fix: No scope",
         ];
 
-        let changes = changes_from_commit_messages(
-            &commits,
-            Some(&vec![String::from("scope")]),
+        let changes = changes_from_commits(
+            &commits.map(Commit::from),
+            &CommitFilter {
+                scopes: Some(vec![String::from("scope")]),
+                ignore: Vec::new(),
+            },
             &Sections::default(),
+            false,
         )
         .collect_vec();
         assert_eq!(
@@ -252,16 +650,20 @@ mod tests {
                 Change {
                     change_type: ChangeType::Feature,
                     description: "Scoped feature".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from(
                         "feat(scope): Scoped feature"
                     )),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
                 },
                 Change {
                     change_type: ChangeType::Fix,
                     description: "No scope".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from(
                         "fix: No scope"
                     )),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
                 },
             ]
         );
@@ -270,14 +672,22 @@ mod tests {
     #[test]
     fn custom_footers() {
         let commits = ["chore: ignored type\n\nignored-footer: ignored\ncustom-footer: hello"];
-        let changelog_sections = Sections(vec![(
-            "custom section".into(),
-            vec![ChangeType::Custom(SectionSource::CommitFooter(
-                "custom-footer".into(),
-            ))],
-        )]);
-        let changes =
-            changes_from_commit_messages(&commits, None, &changelog_sections).collect_vec();
+        let changelog_sections = Sections {
+            sections: vec![(
+                "custom section".into(),
+                vec![ChangeType::Custom(SectionSource::CommitFooter(
+                    "custom-footer".into(),
+                ))],
+            )],
+            bumps: Vec::new(),
+        };
+        let changes = changes_from_commits(
+            &commits.map(Commit::from),
+            &CommitFilter::default(),
+            &changelog_sections,
+            false,
+        )
+        .collect_vec();
         assert_eq!(
             changes,
             vec![Change {
@@ -285,10 +695,325 @@ mod tests {
                     "custom-footer".into()
                 )),
                 description: "hello".into(),
-                original_source: ChangeSource::ConventionalCommit(String::from(
+                original_source: ChangeSource::ConventionalCommit(CommitSource::from(
                     "chore: ignored type\n\tContaining footer custom-footer: hello"
                 )),
+                skip_release_notes: false,
+                issues: Vec::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn custom_types() {
+        let commits = ["perf: faster", "docs: ignored type", "Perf(scope): scoped"];
+        let changelog_sections = Sections {
+            sections: vec![(
+                "Performance".into(),
+                vec![ChangeType::Custom(SectionSource::CustomChangeType(
+                    "perf".into(),
+                ))],
+            )],
+            bumps: Vec::new(),
+        };
+        let changes = changes_from_commits(
+            &commits.map(Commit::from),
+            &CommitFilter::default(),
+            &changelog_sections,
+            false,
+        )
+        .collect_vec();
+        assert_eq!(
+            changes,
+            vec![
+                Change {
+                    change_type: ChangeType::Custom(SectionSource::CustomChangeType("perf".into())),
+                    description: "faster".into(),
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from(
+                        "perf: faster"
+                    )),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
+                },
+                Change {
+                    change_type: ChangeType::Custom(SectionSource::CustomChangeType("perf".into())),
+                    description: "scoped".into(),
+                    original_source: ChangeSource::ConventionalCommit(CommitSource::from(
+                        "Perf(scope): scoped"
+                    )),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn split_squash_commits() {
+        let commits = ["feat: Squashed PR (#12)\n\n* feat: Add X\n\nMore about X\n\nBREAKING CHANGE: X replaces Y\n\n* fix: Fix Z\n\n* not a conventional commit\n\nChangelog-Note: A note about Z"];
+        let changes = changes_from_commits(
+            &commits.map(Commit::from),
+            &CommitFilter::default(),
+            &Sections::default(),
+            true,
+        )
+        .collect_vec();
+        assert_eq!(
+            changes,
+            vec![
+                Change {
+                    change_type: ChangeType::Breaking,
+                    description: "X replaces Y".into(),
+                    original_source: ChangeSource::ConventionalCommit(CommitSource {
+                        summary: String::from(
+                            "feat: Add X\n\tContaining footer BREAKING CHANGE: X replaces Y"
+                        ),
+                        pull_request: Some(12),
+                        ..CommitSource::default()
+                    }),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
+                },
+                Change {
+                    change_type: ChangeType::Feature,
+                    description: "Add X".into(),
+                    original_source: ChangeSource::ConventionalCommit(CommitSource {
+                        summary: String::from("feat: Add X"),
+                        pull_request: Some(12),
+                        ..CommitSource::default()
+                    }),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
+                },
+                Change {
+                    change_type: CommitFooter::from("Changelog-Note").into(),
+                    description: "A note about Z".into(),
+                    original_source: ChangeSource::ConventionalCommit(CommitSource {
+                        summary: String::from(
+                            "fix: Fix Z\n\tContaining footer Changelog-Note: A note about Z"
+                        ),
+                        pull_request: Some(12),
+                        ..CommitSource::default()
+                    }),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
+                },
+                Change {
+                    change_type: ChangeType::Fix,
+                    description: "Fix Z".into(),
+                    original_source: ChangeSource::ConventionalCommit(CommitSource {
+                        summary: String::from("fix: Fix Z"),
+                        pull_request: Some(12),
+                        ..CommitSource::default()
+                    }),
+                    skip_release_notes: false,
+                    issues: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn squash_commits_not_split_by_default() {
+        let commits = ["fix: Squashed PR\n\n* feat: Add X"];
+        let changes = changes_from_commits(
+            &commits.map(Commit::from),
+            &CommitFilter::default(),
+            &Sections::default(),
+            false,
+        )
+        .collect_vec();
+        assert_eq!(
+            changes,
+            vec![Change {
+                change_type: ChangeType::Fix,
+                description: "Squashed PR".into(),
+                original_source: ChangeSource::ConventionalCommit(CommitSource::from(
+                    "fix: Squashed PR"
+                )),
+                skip_release_notes: false,
+                issues: Vec::new(),
             }]
         );
     }
+
+    #[test]
+    fn skip_trailer() {
+        let commits = ["fix: Internal only\n\nChangelog: skip\nChangelog-Note: Also skipped"];
+        let changes = changes_from_commits(
+            &commits.map(Commit::from),
+            &CommitFilter::default(),
+            &Sections::default(),
+            false,
+        )
+        .collect_vec();
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| (&change.change_type, change.skip_release_notes))
+                .collect_vec(),
+            vec![
+                (&CommitFooter::from("Changelog-Note").into(), true),
+                (&ChangeType::Fix, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn description_trailer() {
+        let commits = ["feat: bad description\n\nChangelog-Description: A much better one"];
+        let changes = changes_from_commits(
+            &commits.map(Commit::from),
+            &CommitFilter::default(),
+            &Sections::default(),
+            false,
+        )
+        .collect_vec();
+        assert_eq!(
+            changes,
+            vec![Change {
+                change_type: ChangeType::Feature,
+                description: "A much better one".into(),
+                original_source: ChangeSource::ConventionalCommit(CommitSource::from(
+                    "feat: bad description"
+                )),
+                skip_release_notes: false,
+                issues: Vec::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn issue_references() {
+        let commits = ["fix: #12 crash", "feat: Fixes #3 too\n\nCloses #4"];
+        let changes = changes_from_commits(
+            &commits.map(Commit::from),
+            &CommitFilter::default(),
+            &Sections::default(),
+            false,
+        )
+        .collect_vec();
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| change.issues.as_slice())
+                .collect_vec(),
+            vec![
+                [].as_slice(),
+                &[IssueReference::Number(3), IssueReference::Number(4)]
+            ]
+        );
+    }
+
+    #[test]
+    fn ignore_patterns() {
+        let commits = [
+            "fix(deps): Update something",
+            "fix: Real fix",
+            "feat: WIP thing",
+        ];
+        let filter = CommitFilter {
+            scopes: None,
+            ignore: vec![
+                Regex::new(r"^\w+\(deps\)").unwrap(),
+                Regex::new("WIP").unwrap(),
+            ],
+        };
+        let changes = changes_from_commits(
+            &commits.map(Commit::from),
+            &filter,
+            &Sections::default(),
+            false,
+        )
+        .collect_vec();
+        assert_eq!(
+            changes,
+            vec![Change {
+                change_type: ChangeType::Fix,
+                description: "Real fix".into(),
+                original_source: ChangeSource::ConventionalCommit(CommitSource::from(
+                    "fix: Real fix"
+                )),
+                skip_release_notes: false,
+                issues: Vec::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn commit_details() {
+        let author = Author {
+            name: String::from("Alice"),
+            email: String::from("1234+alice@users.noreply.github.com"),
+            login: None,
+        };
+        let commits = [Commit {
+            message: String::from(
+                "fix: Fix a thing (#123)\n\nChangelog-Note: A note\nCo-authored-by: Bob Smith <bob@example.com>",
+            ),
+            hash: Some(String::from("abc1234def")),
+            author: Some(author.clone()),
+        }];
+        let changes = changes_from_commits(
+            &commits,
+            &CommitFilter::default(),
+            &Sections::default(),
+            false,
+        )
+        .collect_vec();
+        let sources = changes
+            .into_iter()
+            .map(|change| change.original_source)
+            .collect_vec();
+        let expected = |summary: &str| {
+            ChangeSource::ConventionalCommit(CommitSource {
+                summary: String::from(summary),
+                hash: Some(String::from("abc1234def")),
+                author: Some(author.clone()),
+                co_authors: vec![Author {
+                    name: String::from("Bob Smith"),
+                    email: String::from("bob@example.com"),
+                    login: None,
+                }],
+                pull_request: Some(123),
+            })
+        };
+        assert_eq!(
+            sources,
+            vec![
+                expected("fix: Fix a thing (#123)\n\tContaining footer Changelog-Note: A note"),
+                expected("fix: Fix a thing (#123)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn author_username() {
+        let mut author = Author {
+            name: String::from("Alice"),
+            email: String::from("1234+alice@users.noreply.github.com"),
+            login: None,
+        };
+        assert_eq!(author.username(), Some("alice"));
+        author.email = String::from("alice@users.noreply.github.com");
+        assert_eq!(author.username(), Some("alice"));
+        author.email = String::from("alice@example.com");
+        assert_eq!(author.username(), None);
+        author.login = Some(String::from("alice-smith"));
+        assert_eq!(author.username(), Some("alice-smith"));
+    }
+
+    #[test]
+    fn parse_author() {
+        assert_eq!(
+            Author::parse(" Bob Smith <bob@example.com> "),
+            Some(Author {
+                name: String::from("Bob Smith"),
+                email: String::from("bob@example.com"),
+                login: None,
+            })
+        );
+        assert_eq!(Author::parse("Bob Smith"), None);
+        assert_eq!(Author::parse("Bob Smith <>"), None);
+    }
 }
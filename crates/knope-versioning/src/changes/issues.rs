@@ -0,0 +1,143 @@
+use std::fmt::{self, Display};
+
+/// Words which, when followed by issue references, link a change to those issues.
+///
+/// Like `Closes #123`, `Fixes ABC-42`, or `Refs: #9, #10`.
+const KEYWORDS: &[&str] = &[
+    "close",
+    "closes",
+    "closed",
+    "fix",
+    "fixes",
+    "fixed",
+    "resolve",
+    "resolves",
+    "resolved",
+    "ref",
+    "refs",
+    "references",
+];
+
+/// An issue that a change refers to.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum IssueReference {
+    /// An issue or pull request number on GitHub or Gitea, like `#123`
+    Number(u64),
+    /// A Jira issue key, like `ABC-42`
+    Jira(String),
+}
+
+impl IssueReference {
+    pub(super) fn parse(word: &str) -> Option<Self> {
+        let word = word
+            .trim_start_matches('(')
+            .trim_end_matches(['.', ';', ')']);
+        if let Some(number) = word.strip_prefix('#') {
+            return number.parse().ok().map(Self::Number);
+        }
+        let (project, number) = word.rsplit_once('-')?;
+        let valid_project = project.starts_with(|c: char| c.is_ascii_uppercase())
+            && project
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+        let valid_number = !number.is_empty() && number.chars().all(|c| c.is_ascii_digit());
+        (valid_project && valid_number).then(|| Self::Jira(word.to_string()))
+    }
+
+    /// Whether this reference belongs to the Jira `project` (if any), non-Jira references always do.
+    pub(crate) fn is_for_jira_project(&self, project: Option<&str>) -> bool {
+        match self {
+            Self::Number(_) => true,
+            Self::Jira(key) => project.is_some_and(|project| {
+                key.strip_prefix(project)
+                    .is_some_and(|rest| rest.starts_with('-'))
+            }),
+        }
+    }
+}
+
+impl Display for IssueReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "#{number}"),
+            Self::Jira(key) => write!(f, "{key}"),
+        }
+    }
+}
+
+/// Whether `word` (like a commit footer token) is a keyword which refers to issues.
+pub(crate) fn is_keyword(word: &str) -> bool {
+    KEYWORDS
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(word))
+}
+
+/// Find every issue referenced after a keyword (like `Closes #123`) in `text`, without duplicates.
+pub(crate) fn parse_issue_references(text: &str) -> Vec<IssueReference> {
+    let mut references = Vec::new();
+    for line in text.lines() {
+        let mut words = line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty())
+            .peekable();
+        while let Some(word) = words.next() {
+            let keyword = word.trim_end_matches(':');
+            if !is_keyword(keyword) {
+                continue;
+            }
+            while let Some(next) = words.peek() {
+                if next.eq_ignore_ascii_case("and") {
+                    words.next();
+                    continue;
+                }
+                let Some(reference) = IssueReference::parse(next) else {
+                    break;
+                };
+                words.next();
+                if !references.contains(&reference) {
+                    references.push(reference);
+                }
+            }
+        }
+    }
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn keywords_and_lists() {
+        let text =
+            "feat: Add a fix for things\n\nCloses #123, #124 and ABC-42.\nRefs: #9\nfixes #123";
+        assert_eq!(
+            parse_issue_references(text),
+            vec![
+                IssueReference::Number(123),
+                IssueReference::Number(124),
+                IssueReference::Jira(String::from("ABC-42")),
+                IssueReference::Number(9),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_keyword() {
+        assert_eq!(
+            parse_issue_references("fix: Something about #12 and ABC-1 (#5)"),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn jira_project() {
+        let key = IssueReference::Jira(String::from("ABC-42"));
+        assert!(key.is_for_jira_project(Some("ABC")));
+        assert!(!key.is_for_jira_project(Some("AB")));
+        assert!(!key.is_for_jira_project(None));
+        assert!(IssueReference::Number(1).is_for_jira_project(None));
+    }
+}
@@ -1,15 +1,22 @@
 use std::{fmt::Display, sync::Arc};
 
-pub use changeset::CHANGESET_DIR;
+pub use changeset::{
+    parse_change_file, ChangeFile, ChangeFileError, ChangeFileProblem, ChangeFiles, ListedPackage,
+    Metadata, MetadataError, CHANGESET_DIR, METADATA_KEYS,
+};
 use git_conventional::FooterToken;
+pub use issues::IssueReference;
+use relative_path::RelativePathBuf;
 
 use crate::{
+    changes::conventional_commit::{Author, CommitSource},
     package,
     release_notes::{CommitFooter, CustomChangeType, SectionSource},
 };
 
 mod changeset;
 pub mod conventional_commit;
+mod issues;
 
 /// A change to one or more packages.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -17,27 +24,76 @@ pub struct Change {
     pub change_type: ChangeType,
     pub description: Arc<str>,
     pub original_source: ChangeSource,
+    /// Still affects the version, but is left out of release notes (e.g., from `Changelog: skip`).
+    pub skip_release_notes: bool,
+    /// Issues the change refers to, like from `Closes #123`
+    pub issues: Vec<IssueReference>,
 }
 
 impl Change {
+    /// Everyone who contributed to this change, as far as is known.
+    ///
+    /// The `authors` listed in a change file replace the author of the commit which added it.
+    pub fn authors(&self) -> impl Iterator<Item = &Author> {
+        let (author, co_authors) = match &self.original_source {
+            ChangeSource::ConventionalCommit(CommitSource {
+                author, co_authors, ..
+            }) => (author.as_ref(), co_authors.as_slice()),
+            ChangeSource::ChangeFile { authors, .. } if !authors.is_empty() => {
+                (None, authors.as_slice())
+            }
+            ChangeSource::ChangeFile { author, .. } => (author.as_ref(), &[][..]),
+        };
+        author.into_iter().chain(co_authors)
+    }
+
     pub fn from_changesets<'a>(
         package_name: &'a package::Name,
-        releases: &'a [changesets::Release],
+        change_files: &'a ChangeFiles,
     ) -> impl Iterator<Item = Self> + 'a {
-        releases
-            .iter()
-            .find(|release| *package_name == release.package_name)
-            .into_iter()
-            .flat_map(|release_changes| release_changes.changes.clone().into_iter().map(Self::from))
+        change_files.directories.iter().flat_map(move |directory| {
+            directory
+                .releases
+                .iter()
+                .find(|release| *package_name == release.package_name)
+                .into_iter()
+                .flat_map(move |release_changes| {
+                    release_changes.changes.iter().map(move |package_change| {
+                        let metadata = directory
+                            .metadata
+                            .get(&package_change.unique_id.to_file_name())
+                            .cloned()
+                            .unwrap_or_default();
+                        let path = directory.file_path(&package_change.unique_id);
+                        Self::from_change_file(package_change.clone(), path, metadata)
+                    })
+                })
+        })
     }
-}
 
-impl From<changesets::PackageChange> for Change {
-    fn from(package_change: changesets::PackageChange) -> Self {
+    fn from_change_file(
+        package_change: changesets::PackageChange,
+        path: RelativePathBuf,
+        metadata: Metadata,
+    ) -> Self {
+        let mut issues = metadata.issues;
+        for issue in issues::parse_issue_references(&package_change.summary) {
+            if !issues.contains(&issue) {
+                issues.push(issue);
+            }
+        }
         Self {
             change_type: package_change.change_type.into(),
+            issues,
             description: package_change.summary,
-            original_source: ChangeSource::ChangeFile(package_change.unique_id),
+            original_source: ChangeSource::ChangeFile {
+                id: package_change.unique_id,
+                path,
+                author: None,
+                authors: metadata.authors,
+                pull_request: metadata.pull_request,
+            },
+            skip_release_notes: false,
         }
     }
 }
@@ -126,15 +182,25 @@ impl From<FooterToken<'_>> for ChangeType {
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ChangeSource {
-    ConventionalCommit(String),
-    ChangeFile(Arc<changesets::UniqueId>),
+    ConventionalCommit(CommitSource),
+    ChangeFile {
+        id: Arc<changesets::UniqueId>,
+        /// Where the change file is, relative to the root of the project
+        path: RelativePathBuf,
+        /// The author of the commit which added the change file, if known
+        author: Option<Author>,
+        /// Everyone listed in the `authors` metadata of the change file
+        authors: Vec<Author>,
+        /// The pull request from the `pr` metadata of the change file
+        pull_request: Option<u64>,
+    },
 }
 
 impl Display for ChangeSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::ConventionalCommit(commit) => write!(f, "commit {commit}"),
-            Self::ChangeFile(id) => write!(f, "changeset {}", id.to_file_name()),
+            Self::ChangeFile { id, .. } => write!(f, "changeset {}", id.to_file_name()),
         }
     }
 }
@@ -5,7 +5,6 @@ use std::{
     ops::Deref,
 };
 
-use changesets::Release;
 #[cfg(feature = "miette")]
 use miette::Diagnostic;
 use relative_path::RelativePathBuf;
@@ -16,10 +15,14 @@ use tracing::debug;
 use crate::{
     action::Action,
     changes::{
-        conventional_commit::changes_from_commit_messages, Change, ChangeSource, CHANGESET_DIR,
+        conventional_commit::{changes_from_commits, Commit, CommitFilter},
+        Change, ChangeFiles, ChangeSource,
+    },
+    release_notes::{self, ReleaseNotes},
+    semver::{
+        BuildMetadata, Channels, Label, PackageVersions, PreReleaseNotFound, Prerelease, Rule,
+        StableRule, Version,
     },
-    release_notes::{ReleaseNotes, TimeError},
-    semver::{Label, PackageVersions, PreReleaseNotFound, Rule, StableRule, Version},
     versioned_file,
     versioned_file::{cargo, Config, Format, GoVersioning, SetError, VersionedFile},
     PackageNewError::CargoLockNoDependency,
@@ -31,7 +34,7 @@ pub struct Package {
     pub versions: PackageVersions,
     versioned_files: Vec<Config>,
     pub release_notes: ReleaseNotes,
-    scopes: Option<Vec<String>>,
+    commit_filter: CommitFilter,
 }
 
 impl Package {
@@ -46,13 +49,15 @@ impl Package {
         versioned_files_tracked: Vec<Config>,
         all_versioned_files: &[VersionedFile],
         release_notes: ReleaseNotes,
-        scopes: Option<Vec<String>>,
+        commit_filter: CommitFilter,
+        prerelease_channels: Channels,
     ) -> Result<Self, Box<NewError>> {
         let (versioned_files, version_from_files) =
             validate_versioned_files(versioned_files_tracked, all_versioned_files)?;
 
         debug!("Looking for Git tags matching package name.");
-        let mut versions = PackageVersions::from_tags(name.as_custom(), git_tags);
+        let mut versions =
+            PackageVersions::from_tags(name.as_custom(), git_tags, prerelease_channels);
         if let Some(version_from_files) = version_from_files {
             versions.update_version(version_from_files);
         }
@@ -62,7 +67,7 @@ impl Package {
             versions,
             versioned_files,
             release_notes,
-            scopes,
+            commit_filter,
         })
     }
 
@@ -89,8 +94,20 @@ impl Package {
             Bump::Manual(version) => {
                 self.versions.update_version(version);
             }
-            Bump::Rule(rule) => {
+            Bump::Rule {
+                rule,
+                build_metadata,
+            } => {
                 self.versions.bump(rule)?;
+                self.versions.set_build_metadata(build_metadata);
+            }
+            Bump::Snapshot {
+                stable_rule,
+                prerelease,
+                build_metadata,
+            } => {
+                self.versions.snapshot(stable_rule, prerelease);
+                self.versions.set_build_metadata(build_metadata);
             }
         };
         let version = self.versions.clone().into_latest();
@@ -111,14 +128,52 @@ impl Package {
             .collect()
     }
 
+    /// The scopes that conventional commits must have (if any) to apply to this package.
     #[must_use]
-    pub fn get_changes(&self, changeset: &[Release], commit_messages: &[String]) -> Vec<Change> {
-        changes_from_commit_messages(
-            commit_messages,
-            self.scopes.as_ref(),
+    pub fn scopes(&self) -> Option<&[String]> {
+        self.commit_filter.scopes.as_deref()
+    }
+
+    /// Whether a commit is ignored entirely by this package, like by `ignore_commits`.
+    #[must_use]
+    pub fn ignores_commit(&self, message: &str) -> bool {
+        self.commit_filter
+            .ignore
+            .iter()
+            .any(|pattern| pattern.is_match(message))
+    }
+
+    /// Get the changes for this package from change files and conventional commits.
+    ///
+    /// If `split_squash_commits` is set, bulleted commits in the bodies of squash merges are each
+    /// treated as their own commit.
+    ///
+    /// Jira issue references are only kept if they belong to the configured Jira project.
+    #[must_use]
+    pub fn get_changes(
+        &self,
+        change_files: &ChangeFiles,
+        commits: &[Commit],
+        split_squash_commits: bool,
+    ) -> Vec<Change> {
+        changes_from_commits(
+            commits,
+            &self.commit_filter,
             &self.release_notes.sections,
+            split_squash_commits,
         )
-        .chain(Change::from_changesets(&self.name, changeset))
+        .chain(Change::from_changesets(&self.name, change_files))
+        .map(|mut change| {
+            let jira_project = self
+                .release_notes
+                .jira
+                .as_ref()
+                .map(|jira| jira.project.as_str());
+            change
+                .issues
+                .retain(|issue| issue.is_for_jira_project(jira_project));
+            change
+        })
         .collect()
     }
 
@@ -151,9 +206,13 @@ impl Package {
             }
             ChangeConfig::Calculate {
                 prerelease_label,
+                build_metadata,
                 go_versioning,
             } => {
-                let stable_rule = StableRule::from(changes);
+                let Some(stable_rule) = self.release_notes.sections.stable_rule(changes) else {
+                    debug!("No changes bump the version");
+                    return Ok((versioned_files, Vec::new()));
+                };
                 let rule = if let Some(pre_label) = prerelease_label {
                     Rule::Pre {
                         label: pre_label.clone(),
@@ -162,21 +221,45 @@ impl Package {
                 } else {
                     stable_rule.into()
                 };
-                self.bump_version(Bump::Rule(rule), go_versioning, versioned_files)?
+                self.bump_version(
+                    Bump::Rule {
+                        rule,
+                        build_metadata,
+                    },
+                    go_versioning,
+                    versioned_files,
+                )?
+            }
+            ChangeConfig::Snapshot {
+                prerelease,
+                build_metadata,
+                go_versioning,
+            } => {
+                let Some(stable_rule) = self.release_notes.sections.stable_rule(changes) else {
+                    debug!("No changes bump the version");
+                    return Ok((versioned_files, Vec::new()));
+                };
+                let updated = self.bump_version(
+                    Bump::Snapshot {
+                        stable_rule,
+                        prerelease,
+                        build_metadata,
+                    },
+                    go_versioning,
+                    versioned_files,
+                )?;
+                return Ok((updated, Vec::new()));
             }
         };
         let version = self.versions.clone().into_latest();
         let mut actions: Vec<Action> = changes
             .iter()
             .filter_map(|change| {
-                if let ChangeSource::ChangeFile(unique_id) = &change.original_source {
+                if let ChangeSource::ChangeFile { path, .. } = &change.original_source {
                     if version.is_prerelease() {
                         None
                     } else {
-                        Some(Action::RemoveFile {
-                            path: RelativePathBuf::from(CHANGESET_DIR)
-                                .join(unique_id.to_file_name()),
-                        })
+                        Some(Action::RemoveFile { path: path.clone() })
                     }
                 } else {
                     None
@@ -184,10 +267,12 @@ impl Package {
             })
             .collect();
 
-        actions.extend(
-            self.release_notes
-                .create_release(version, changes, &self.name)?,
-        );
+        actions.extend(self.release_notes.create_release(
+            version,
+            changes,
+            &self.name,
+            self.versions.channels(),
+        )?);
 
         Ok((updated, actions))
     }
@@ -229,9 +314,9 @@ fn validate_versioned_files(
             if *first_version != version {
                 return Err(NewError::InconsistentVersions {
                     first_path: first_versioned_file.path().clone(),
-                    first_version: first_version.clone(),
+                    first_version: Box::new(first_version.clone()),
                     second_path: versioned_file.path().clone(),
-                    second_version: version,
+                    second_version: Box::new(version),
                 }
                 .into());
             }
@@ -278,6 +363,16 @@ pub enum ChangeConfig {
     Force(Version),
     Calculate {
         prerelease_label: Option<Label>,
+        build_metadata: Option<BuildMetadata>,
+        go_versioning: GoVersioning,
+    },
+    /// Calculate the next version, then use `prerelease` as its pre-release component.
+    ///
+    /// Snapshots only update versioned files, they don't create release notes or remove change
+    /// files.
+    Snapshot {
+        prerelease: Prerelease,
+        build_metadata: Option<BuildMetadata>,
         go_versioning: GoVersioning,
     },
 }
@@ -296,9 +391,9 @@ pub enum NewError {
     )]
     InconsistentVersions {
         first_path: RelativePathBuf,
-        first_version: Version,
+        first_version: Box<Version>,
         second_path: RelativePathBuf,
-        second_version: Version,
+        second_version: Box<Version>,
     },
     #[error("Versioned file not found: {0}")]
     #[cfg_attr(
@@ -422,7 +517,17 @@ impl PartialEq<String> for Name {
 
 pub enum Bump {
     Manual(Version),
-    Rule(Rule),
+    Rule {
+        rule: Rule,
+        /// Attached to the version after applying `rule`
+        build_metadata: Option<BuildMetadata>,
+    },
+    /// Apply `stable_rule`, then use `prerelease` as the pre-release component.
+    Snapshot {
+        stable_rule: StableRule,
+        prerelease: Prerelease,
+        build_metadata: Option<BuildMetadata>,
+    },
 }
 
 #[derive(Debug, Error)]
@@ -435,5 +540,5 @@ pub enum BumpError {
     PreReleaseNotFound(#[from] PreReleaseNotFound),
     #[error(transparent)]
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
-    Time(#[from] TimeError),
+    ReleaseNotes(#[from] release_notes::Error),
 }
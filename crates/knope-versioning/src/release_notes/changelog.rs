@@ -1,11 +1,17 @@
-use std::{fmt::Display, str::FromStr};
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
 
 use itertools::Itertools;
 use relative_path::RelativePathBuf;
 use thiserror::Error;
 use time::{macros::format_description, Date};
 
-use crate::{package, release_notes::Release, semver::Version};
+use super::Forge;
+use crate::{
+    package,
+    release_notes::Release,
+    semver::{Channels, Version},
+    ReleaseTag,
+};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Changelog {
@@ -65,24 +71,16 @@ impl Changelog {
     /// Find a release matching `version`, if any, within the changelog.
     #[must_use]
     pub fn get_release(&self, version: &Version, package_name: &package::Name) -> Option<Release> {
-        let expected_header_start = format!(
-            "{release_header_level} {version}",
-            release_header_level = self.release_header_level
-        );
-
         let mut lines = self.content.lines();
         let (title, version) = loop {
             let line = lines.next()?;
-            if !line.starts_with(&expected_header_start) {
-                continue;
-            }
             let Ok((header_level, title_version, _)) = parse_title(line) else {
                 continue;
             };
             if header_level == self.release_header_level && *version == title_version {
                 break (
                     // Release titles should not be markdown formatted
-                    line.trim_start_matches('#').trim().to_string(),
+                    unlinked_title(line.trim_start_matches('#').trim()),
                     title_version,
                 );
             }
@@ -93,7 +91,7 @@ impl Changelog {
                     // Next version
                     "{release_header_level} ",
                     release_header_level = self.release_header_level
-                ))
+                )) && !is_version_link(line)
             })
             .map(|line| match self.release_header_level {
                 HeaderLevel::H1 => line,
@@ -108,32 +106,177 @@ impl Changelog {
         })
     }
 
+    /// Whether the changelog already has a title for `version`, even if it has no notes.
+    #[must_use]
+    pub fn has_release(&self, version: &Version) -> bool {
+        self.content.lines().any(|line| {
+            parse_title(line).is_ok_and(|(header_level, title_version, _)| {
+                header_level == self.release_header_level && title_version == *version
+            })
+        })
+    }
+
     /// Update `self.content` with the new release, return the diff being applied.
+    ///
+    /// If there's an "Unreleased" section, anything written in it by hand is merged into the
+    /// release, which goes right after that section, leaving it empty.
+    ///
+    /// If the changelog already links versions to `forge` with reference definitions (like
+    /// `[1.0.0]: https://github.com/owner/repo/compare/v0.1.0...v1.0.0`), the new release title
+    /// links to one comparing it with the previous release (ordered by `channels`).
     #[must_use]
-    pub fn with_release(&mut self, release: &Release) -> String {
-        let mut not_written = true;
-        let new_changes = format!(
-            "{header_level} {title}\n\n{body}",
-            header_level = self.release_header_level,
-            title = release.title,
-            body = release
-                .notes
-                .lines()
-                .map(|line| {
-                    // Release notes are at H1, we need to format them properly for this changelog
-                    if line.starts_with('#') && self.release_header_level == HeaderLevel::H2 {
-                        format!("#{line}")
-                    } else {
-                        line.to_string()
-                    }
+    pub fn with_release(
+        &mut self,
+        release: &Release,
+        forge: Option<&Forge>,
+        channels: &Channels,
+    ) -> String {
+        let Some(forge) = forge.filter(|_| self.content.lines().any(is_version_link)) else {
+            return self.insert_unreleased_release(release);
+        };
+        let version = release.version.to_string();
+        let linked_release = Release {
+            title: release.title.strip_prefix(&version).map_or_else(
+                || release.title.clone(),
+                |rest| format!("[{version}]{rest}"),
+            ),
+            ..release.clone()
+        };
+        let mut new_changes = self.insert_unreleased_release(&linked_release);
+        let links = self.with_version_links(release, forge, channels);
+        if !links.is_empty() {
+            new_changes.push_str("\n\n");
+            new_changes.push_str(&links.join("\n"));
+        }
+        new_changes
+    }
+
+    /// Insert `release` after the "Unreleased" section if there is one, otherwise before the
+    /// first release.
+    fn insert_unreleased_release(&mut self, release: &Release) -> String {
+        let Some(unreleased) = self.unreleased_section() else {
+            return self.insert_release(release, |_| true, false);
+        };
+        let notes = merge_notes(&release.notes, &self.hand_written(&unreleased));
+        let new_changes = self.format_release(&release.title, &notes);
+        let lines = self.content.lines().collect_vec();
+        let mut new_content = lines
+            .get(..=unreleased.header)
+            .unwrap_or_default()
+            .join("\n");
+        new_content.push_str("\n\n");
+        new_content.push_str(&new_changes);
+        let rest = lines.get(unreleased.end..).unwrap_or_default();
+        if !rest.is_empty() {
+            new_content.push_str("\n\n");
+            new_content.push_str(&rest.join("\n"));
+        }
+        self.replace_content(new_content);
+        new_changes
+    }
+
+    /// Like [`Self::with_release`], but for a release which might be older than those already in
+    /// the changelog. It's inserted before the first release with a lower version, ordered by
+    /// `channels`.
+    #[must_use]
+    pub fn with_past_release(&mut self, release: &Release, channels: &Channels) -> String {
+        self.insert_release(
+            release,
+            |version| channels.cmp_versions(version, &release.version) == Ordering::Less,
+            true,
+        )
+    }
+
+    /// What was written by hand in the "Unreleased" section, at header level 1 like release notes.
+    ///
+    /// Returns `None` if there is no such section, or it only has pending changes.
+    #[must_use]
+    pub fn unreleased(&self) -> Option<String> {
+        self.unreleased_section()
+            .map(|section| self.hand_written(&section))
+            .filter(|notes| !notes.is_empty())
+    }
+
+    /// Replace the pending changes at the top of the "Unreleased" section with `notes` (at header
+    /// level 1), keeping anything written there by hand. Returns the new section.
+    ///
+    /// If there's no "Unreleased" section, one is added before the first release.
+    #[must_use]
+    pub fn with_unreleased(&mut self, notes: &str) -> String {
+        let lines = self.content.lines().collect_vec();
+        let (before, title, hand_written, after) = if let Some(section) = self.unreleased_section()
+        {
+            (
+                lines.get(..section.header).unwrap_or_default(),
+                lines
+                    .get(section.header)
+                    .copied()
+                    .unwrap_or_default()
+                    .to_string(),
+                self.hand_written_lines(&section).join("\n"),
+                lines.get(section.end..).unwrap_or_default(),
+            )
+        } else {
+            let first_release = lines
+                .iter()
+                .position(|line| {
+                    parse_title(line).is_ok_and(|(level, _, _)| level == self.release_header_level)
+                        || is_version_link(line)
                 })
-                .join("\n")
-        );
+                .unwrap_or(lines.len());
+            (
+                lines.get(..first_release).unwrap_or_default(),
+                format!("{} Unreleased", self.release_header_level),
+                String::new(),
+                lines.get(first_release..).unwrap_or_default(),
+            )
+        };
+
+        let mut section = title;
+        if !notes.is_empty() {
+            section.push_str(&format!(
+                "\n\n{PENDING_START}\n\n{}\n\n{PENDING_END}",
+                self.adjust_header_levels(notes)
+            ));
+        }
+        let hand_written = hand_written.trim();
+        if !hand_written.is_empty() {
+            section.push_str("\n\n");
+            section.push_str(hand_written);
+        }
+
+        let mut new_content = before.join("\n").trim_end().to_string();
+        if !new_content.is_empty() {
+            new_content.push_str("\n\n");
+        }
+        new_content.push_str(&section);
+        if !after.is_empty() {
+            new_content.push_str("\n\n");
+            new_content.push_str(&after.join("\n"));
+        }
+        self.replace_content(new_content);
+        section
+    }
+
+    /// Insert `release` before the first release title whose version matches `insert_before`.
+    ///
+    /// If there is no such title, the release is appended, after a blank line if `separate`.
+    fn insert_release(
+        &mut self,
+        release: &Release,
+        insert_before: impl Fn(&Version) -> bool,
+        separate: bool,
+    ) -> String {
+        let mut not_written = true;
+        let new_changes = self.format_release(&release.title, &release.notes);
         let mut new_content = String::with_capacity(self.content.len() + new_changes.len());
 
         for line in self.content.lines() {
-            if not_written && parse_title(line).is_ok() {
-                // Insert new changes before the next release in the changelog
+            if not_written
+                && (parse_title(line).is_ok_and(|(_, version, _)| insert_before(&version))
+                    || is_version_link(line))
+            {
+                // Insert new changes before the next release (or the links at the end)
                 new_content.push_str(&new_changes);
                 new_content.push_str("\n\n");
                 not_written = false;
@@ -143,18 +286,232 @@ impl Changelog {
         }
 
         if not_written {
+            if separate && !new_content.is_empty() && !new_content.ends_with("\n\n") {
+                new_content.push('\n');
+            }
             new_content.push_str(&new_changes);
         }
 
+        self.replace_content(new_content);
+        new_changes
+    }
+
+    fn format_release(&self, title: &str, notes: &str) -> String {
+        format!(
+            "{header_level} {title}\n\n{body}",
+            header_level = self.release_header_level,
+            body = self.adjust_header_levels(notes)
+        )
+    }
+
+    /// Release notes are at H1, we need to format them properly for this changelog
+    fn adjust_header_levels(&self, notes: &str) -> String {
+        notes
+            .lines()
+            .map(|line| {
+                if line.starts_with('#') && self.release_header_level == HeaderLevel::H2 {
+                    format!("#{line}")
+                } else {
+                    line.to_string()
+                }
+            })
+            .join("\n")
+    }
+
+    fn replace_content(&mut self, mut new_content: String) {
         if (self.content.ends_with('\n') || self.content.is_empty()) && !new_content.ends_with('\n')
         {
             // Preserve white space at end of file
             new_content.push('\n');
         }
-
         self.content = new_content;
-        new_changes
     }
+
+    /// Find the "Unreleased" section, a title like `## [Unreleased]` at the level of releases.
+    fn unreleased_section(&self) -> Option<UnreleasedSection> {
+        let release_title_start = format!("{} ", self.release_header_level);
+        let mut lines = self.content.lines().enumerate();
+        let header = lines.find_map(|(index, line)| {
+            line.strip_prefix(&release_title_start)
+                .map(|title| title.trim().trim_start_matches('[').trim_end_matches(']'))
+                .filter(|title| title.eq_ignore_ascii_case("unreleased"))
+                .map(|_| index)
+        })?;
+        let end = lines
+            .find(|(_, line)| line.starts_with(&release_title_start) || is_version_link(line))
+            .map_or_else(|| self.content.lines().count(), |(index, _)| index);
+        Some(UnreleasedSection { header, end })
+    }
+
+    /// Add a reference definition for the title of `release`, comparing it to the previous
+    /// release, and point the `[Unreleased]` one at changes since `release`.
+    ///
+    /// Returns the definitions that were added or changed.
+    fn with_version_links(
+        &mut self,
+        release: &Release,
+        forge: &Forge,
+        channels: &Channels,
+    ) -> Vec<String> {
+        let tag = ReleaseTag::new(&release.version, &release.package_name);
+        let previous_version = self
+            .content
+            .lines()
+            .filter_map(|line| parse_title(line).ok())
+            .filter(|(level, version, _)| {
+                *level == self.release_header_level
+                    && channels.cmp_versions(version, &release.version) == Ordering::Less
+            })
+            .map(|(_, version, _)| version)
+            .max_by(|version, other| channels.cmp_versions(version, other));
+        let url = previous_version.map_or_else(
+            || forge.release_url(tag.as_str()),
+            |previous| {
+                let previous = ReleaseTag::new(&previous, &release.package_name);
+                forge.compare_url(previous.as_str(), tag.as_str())
+            },
+        );
+        let version = release.version.to_string();
+        let mut version_link = Some(format!("[{version}]: {url}"));
+        let mut changed = Vec::new();
+        let mut lines = Vec::new();
+        for line in self.content.lines() {
+            match link_label(line) {
+                Some(label) if label.eq_ignore_ascii_case("unreleased") => {
+                    let link = format!("[{label}]: {}", forge.compare_url(tag.as_str(), "HEAD"));
+                    lines.push(link.clone());
+                    changed.push(link);
+                }
+                // Replaced by the new definition
+                Some(label) if label == version => {}
+                // The newest version comes first
+                Some(label) if Version::from_str(label).is_ok() => {
+                    if let Some(link) = version_link.take() {
+                        lines.push(link.clone());
+                        changed.push(link);
+                    }
+                    lines.push(line.to_string());
+                }
+                _ => lines.push(line.to_string()),
+            }
+        }
+        if let Some(link) = version_link {
+            lines.push(link.clone());
+            changed.push(link);
+        }
+        self.replace_content(lines.join("\n"));
+        changed
+    }
+
+    /// The lines of `section` which were written by hand, skipping the pending changes.
+    fn hand_written_lines(&self, section: &UnreleasedSection) -> Vec<&str> {
+        let mut pending = false;
+        let mut lines = Vec::new();
+        for line in self
+            .content
+            .lines()
+            .take(section.end)
+            .skip(section.header + 1)
+        {
+            match line.trim() {
+                PENDING_START => pending = true,
+                PENDING_END => pending = false,
+                _ if !pending => lines.push(line),
+                _ => {}
+            }
+        }
+        lines
+    }
+
+    /// What was written by hand in `section`, at header level 1.
+    fn hand_written(&self, section: &UnreleasedSection) -> String {
+        self.hand_written_lines(section)
+            .into_iter()
+            .map(|line| match self.release_header_level {
+                HeaderLevel::H1 => line,
+                HeaderLevel::H2 => reduce_header_level(line),
+            })
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+}
+
+/// Comments around the changes which Knope keeps up to date in the "Unreleased" section.
+const PENDING_START: &str = "<!-- knope:pending -->";
+const PENDING_END: &str = "<!-- /knope:pending -->";
+
+/// Where the "Unreleased" section is, by line index.
+struct UnreleasedSection {
+    header: usize,
+    /// The first line after the section
+    end: usize,
+}
+
+/// Add `hand_written` notes to `generated` ones, both at header level 1.
+///
+/// Sections with the same name (like `## Fixes`) are combined, other hand-written sections come
+/// after the generated ones, and anything before the first section goes first.
+pub(super) fn merge_notes(generated: &str, hand_written: &str) -> String {
+    if hand_written.is_empty() {
+        return generated.to_string();
+    }
+    let (mut preamble, mut sections) = split_sections(generated);
+    let (hand_written_preamble, hand_written_sections) = split_sections(hand_written);
+    preamble = join_blocks(&hand_written_preamble, &preamble);
+    for (name, body) in hand_written_sections {
+        if let Some((_, existing)) = sections
+            .iter_mut()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(&name))
+        {
+            *existing = join_blocks(existing, &body);
+        } else {
+            sections.push((name, body));
+        }
+    }
+    let mut notes = preamble;
+    for (name, body) in sections {
+        notes.push_str(&format!("\n\n## {name}\n\n{body}"));
+    }
+    notes.trim().to_string()
+}
+
+/// Split notes into whatever comes before the first `## ` section, and each section's name and body.
+fn split_sections(notes: &str) -> (String, Vec<(String, String)>) {
+    let mut preamble = Vec::new();
+    let mut sections: Vec<(String, Vec<&str>)> = Vec::new();
+    for line in notes.lines() {
+        if let Some(name) = line.strip_prefix("## ") {
+            sections.push((name.trim().to_string(), Vec::new()));
+        } else if let Some((_, body)) = sections.last_mut() {
+            body.push(line);
+        } else {
+            preamble.push(line);
+        }
+    }
+    (
+        preamble.join("\n").trim().to_string(),
+        sections
+            .into_iter()
+            .map(|(name, body)| (name, body.join("\n").trim().to_string()))
+            .collect(),
+    )
+}
+
+/// Join two blocks of Markdown, keeping lists together.
+fn join_blocks(first: &str, second: &str) -> String {
+    if first.is_empty() || second.is_empty() {
+        return format!("{first}{second}");
+    }
+    let is_list_item = |line: &str| line.starts_with("- ") || line.starts_with("* ");
+    let separator = if first.lines().last().is_some_and(is_list_item)
+        && second.lines().next().is_some_and(is_list_item)
+    {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    format!("{first}{separator}{second}")
 }
 
 fn parse_title(title: &str) -> Result<(HeaderLevel, Version, Option<Date>), ParseError> {
@@ -165,6 +522,11 @@ fn parse_title(title: &str) -> Result<(HeaderLevel, Version, Option<Date>), Pars
         _ => return Err(ParseError::HeaderLevel),
     };
     let version = parts.next().ok_or(ParseError::MissingVersion)?;
+    // The version might be a link, like `[1.0.0]` or `[1.0.0](https://...)`
+    let version = version
+        .strip_prefix('[')
+        .and_then(|linked| linked.split_once(']'))
+        .map_or(version, |(version, _)| version);
     let version = Version::from_str(version).map_err(|_| ParseError::MissingVersion)?;
     let mut date = None;
     for part in parts {
@@ -177,6 +539,37 @@ fn parse_title(title: &str) -> Result<(HeaderLevel, Version, Option<Date>), Pars
     Ok((header_level, version, date))
 }
 
+/// A release title without the link on its version, like `1.0.0 (2023-01-01)` for
+/// `[1.0.0] (2023-01-01)`.
+fn unlinked_title(title: &str) -> String {
+    let Some((version, rest)) = title
+        .strip_prefix('[')
+        .and_then(|linked| linked.split_once(']'))
+    else {
+        return title.to_string();
+    };
+    let rest = if rest.starts_with('(') {
+        rest.split_once(')').map_or(rest, |(_, rest)| rest)
+    } else {
+        rest
+    };
+    format!("{version}{rest}")
+}
+
+/// The label of a link reference definition, like `1.0.0` for `[1.0.0]: https://...`.
+fn link_label(line: &str) -> Option<&str> {
+    line.strip_prefix('[')
+        .and_then(|rest| rest.split_once("]:"))
+        .map(|(label, _)| label)
+}
+
+/// Whether `line` defines where a version (or "Unreleased") title links to.
+fn is_version_link(line: &str) -> bool {
+    link_label(line).is_some_and(|label| {
+        label.eq_ignore_ascii_case("unreleased") || Version::from_str(label).is_ok()
+    })
+}
+
 fn reduce_header_level(line: &str) -> &str {
     if line.starts_with("##") {
         #[allow(clippy::indexing_slicing)] // Just checked len above
@@ -225,6 +618,21 @@ mod test_parse_title {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn linked() {
+        let title = "## [0.1.2] - 2023-05-02";
+        let (header_level, version, date) = parse_title(title).unwrap();
+        assert_eq!(header_level, HeaderLevel::H2);
+        assert_eq!(version, Version::new(0, 1, 2, None));
+        assert_eq!(date, Some(date!(2023 - 05 - 02)));
+
+        let title =
+            "## [0.1.2](https://github.com/knope-dev/knope/releases/tag/v0.1.2) (2023-05-02)";
+        let (_, version, date) = parse_title(title).unwrap();
+        assert_eq!(version, Version::new(0, 1, 2, None));
+        assert_eq!(date, Some(date!(2023 - 05 - 02)));
+    }
+
     #[test]
     fn h1() {
         let title = "# 0.1.2 (2023-05-02)";
@@ -235,6 +643,272 @@ mod test_parse_title {
     }
 }
 
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_with_past_release {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn release(version: &str) -> Release {
+        Release {
+            title: format!("{version} (2023-01-01)"),
+            version: Version::from_str(version).unwrap(),
+            notes: String::from("### Fixes\n\n- A fix"),
+            package_name: package::Name::Default,
+        }
+    }
+
+    #[test]
+    fn between_releases() {
+        let mut changelog = Changelog::new(
+            RelativePathBuf::from("CHANGELOG.md"),
+            String::from("# Changelog\n\n## 2.0.0\n\n- Big\n\n## 1.0.0\n\n- First\n"),
+        );
+        assert!(!changelog.has_release(&Version::from_str("1.1.0").unwrap()));
+        let _ = changelog.with_past_release(&release("1.1.0"), &Channels::default());
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## 2.0.0\n\n- Big\n\n## 1.1.0 (2023-01-01)\n\n#### Fixes\n\n- A fix\n\n## 1.0.0\n\n- First\n"
+        );
+        assert!(changelog.has_release(&Version::from_str("1.1.0").unwrap()));
+    }
+
+    #[test]
+    fn pre_releases_ordered_by_channel() {
+        let content = "# Changelog\n\n## 1.0.0-preview.0\n\n- Preview\n";
+        let mut changelog =
+            Changelog::new(RelativePathBuf::from("CHANGELOG.md"), String::from(content));
+        let channels = Channels::new(vec!["rc".into(), "preview".into()]);
+        let _ = changelog.with_past_release(&release("1.0.0-rc.0"), &channels);
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## 1.0.0-preview.0\n\n- Preview\n\n## 1.0.0-rc.0 (2023-01-01)\n\n#### Fixes\n\n- A fix\n"
+        );
+
+        let mut changelog =
+            Changelog::new(RelativePathBuf::from("CHANGELOG.md"), String::from(content));
+        let _ = changelog.with_past_release(&release("1.0.0-rc.0"), &Channels::default());
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## 1.0.0-rc.0 (2023-01-01)\n\n#### Fixes\n\n- A fix\n\n## 1.0.0-preview.0\n\n- Preview\n"
+        );
+    }
+
+    #[test]
+    fn oldest_release() {
+        let mut changelog = Changelog::new(
+            RelativePathBuf::from("CHANGELOG.md"),
+            String::from("# Changelog\n\n## 2.0.0\n\n- Big\n"),
+        );
+        let _ = changelog.with_past_release(&release("1.0.0"), &Channels::default());
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## 2.0.0\n\n- Big\n\n## 1.0.0 (2023-01-01)\n\n#### Fixes\n\n- A fix\n"
+        );
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_unreleased {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn release() -> Release {
+        Release {
+            title: String::from("1.1.0 (2023-01-01)"),
+            version: Version::from_str("1.1.0").unwrap(),
+            notes: String::from("## Features\n\n- A feature\n\n## Fixes\n\n- A fix"),
+            package_name: package::Name::Default,
+        }
+    }
+
+    fn changelog(content: &str) -> Changelog {
+        Changelog::new(RelativePathBuf::from("CHANGELOG.md"), String::from(content))
+    }
+
+    #[test]
+    fn release_takes_hand_written_notes() {
+        let mut changelog = changelog(
+            "# Changelog\n\n## [Unreleased]\n\nHeads up!\n\n### Fixes\n\n- Another fix\n\n### Security\n\n- Patched\n\n## 1.0.0\n\n- First\n",
+        );
+        assert_eq!(
+            changelog.unreleased().as_deref(),
+            Some("Heads up!\n\n## Fixes\n\n- Another fix\n\n## Security\n\n- Patched")
+        );
+        let _ = changelog.with_release(&release(), None, &Channels::default());
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## [Unreleased]\n\n## 1.1.0 (2023-01-01)\n\nHeads up!\n\n### Features\n\n- A feature\n\n### Fixes\n\n- A fix\n- Another fix\n\n### Security\n\n- Patched\n\n## 1.0.0\n\n- First\n"
+        );
+        assert_eq!(changelog.unreleased(), None);
+    }
+
+    #[test]
+    fn empty_unreleased_section() {
+        let mut changelog = changelog("# Changelog\n\n## Unreleased\n");
+        assert_eq!(changelog.unreleased(), None);
+        let _ = changelog.with_release(&release(), None, &Channels::default());
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## Unreleased\n\n## 1.1.0 (2023-01-01)\n\n### Features\n\n- A feature\n\n### Fixes\n\n- A fix\n"
+        );
+    }
+
+    #[test]
+    fn pending_changes_are_replaced() {
+        let mut changelog =
+            changelog("# Changelog\n\n## [Unreleased]\n\n- Hand written\n\n## 1.0.0\n\n- First\n");
+        let section = changelog.with_unreleased("## Fixes\n\n- A fix");
+        assert_eq!(
+            section,
+            "## [Unreleased]\n\n<!-- knope:pending -->\n\n### Fixes\n\n- A fix\n\n<!-- /knope:pending -->\n\n- Hand written"
+        );
+        let _ = changelog.with_unreleased("## Fixes\n\n- A fix\n- Another fix");
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## [Unreleased]\n\n<!-- knope:pending -->\n\n### Fixes\n\n- A fix\n- Another fix\n\n<!-- /knope:pending -->\n\n- Hand written\n\n## 1.0.0\n\n- First\n"
+        );
+        assert_eq!(changelog.unreleased().as_deref(), Some("- Hand written"));
+
+        // Pending changes aren't merged twice when releasing
+        let _ = changelog.with_release(&release(), None, &Channels::default());
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## [Unreleased]\n\n## 1.1.0 (2023-01-01)\n\n- Hand written\n\n### Features\n\n- A feature\n\n### Fixes\n\n- A fix\n\n## 1.0.0\n\n- First\n"
+        );
+    }
+
+    #[test]
+    fn unreleased_section_is_added() {
+        let mut changelog = changelog("# Changelog\n\n## 1.0.0\n\n- First\n");
+        let _ = changelog.with_unreleased("## Fixes\n\n- A fix");
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## Unreleased\n\n<!-- knope:pending -->\n\n### Fixes\n\n- A fix\n\n<!-- /knope:pending -->\n\n## 1.0.0\n\n- First\n"
+        );
+        let _ = changelog.with_unreleased("");
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## Unreleased\n\n## 1.0.0\n\n- First\n"
+        );
+    }
+
+    #[test]
+    fn merge() {
+        assert_eq!(
+            merge_notes("## Fixes\n\n- A fix", ""),
+            "## Fixes\n\n- A fix"
+        );
+        assert_eq!(
+            merge_notes("", "## Fixes\n\n- A fix"),
+            "## Fixes\n\n- A fix"
+        );
+        assert_eq!(
+            merge_notes(
+                "## Fixes\n\n### A big fix\n\nDetails",
+                "## fixes\n\n- A small fix"
+            ),
+            "## Fixes\n\n### A big fix\n\nDetails\n\n- A small fix"
+        );
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_version_links {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn release(version: &str, package_name: package::Name) -> Release {
+        Release {
+            title: format!("{version} (2023-02-01)"),
+            version: Version::from_str(version).unwrap(),
+            notes: String::from("## Fixes\n\n- A fix"),
+            package_name,
+        }
+    }
+
+    fn forge() -> Forge {
+        Forge::GitHub {
+            owner: String::from("o"),
+            repo: String::from("r"),
+        }
+    }
+
+    #[test]
+    fn links_are_maintained() {
+        let mut changelog = Changelog::new(
+            RelativePathBuf::from("CHANGELOG.md"),
+            String::from("# Changelog\n\n## [Unreleased]\n\n## [1.1.0] - 2023-01-01\n\n- Second\n\n## [1.0.0] - 2022-01-01\n\n- First\n\n[Unreleased]: https://github.com/o/r/compare/v1.1.0...HEAD\n[1.1.0]: https://github.com/o/r/compare/v1.0.0...v1.1.0\n[1.0.0]: https://github.com/o/r/releases/tag/v1.0.0\n"),
+        );
+        let diff = changelog.with_release(
+            &release("1.2.0", package::Name::Default),
+            Some(&forge()),
+            &Channels::default(),
+        );
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## [Unreleased]\n\n## [1.2.0] (2023-02-01)\n\n### Fixes\n\n- A fix\n\n## [1.1.0] - 2023-01-01\n\n- Second\n\n## [1.0.0] - 2022-01-01\n\n- First\n\n[Unreleased]: https://github.com/o/r/compare/v1.2.0...HEAD\n[1.2.0]: https://github.com/o/r/compare/v1.1.0...v1.2.0\n[1.1.0]: https://github.com/o/r/compare/v1.0.0...v1.1.0\n[1.0.0]: https://github.com/o/r/releases/tag/v1.0.0\n"
+        );
+        assert_eq!(
+            diff,
+            "## [1.2.0] (2023-02-01)\n\n### Fixes\n\n- A fix\n\n[Unreleased]: https://github.com/o/r/compare/v1.2.0...HEAD\n[1.2.0]: https://github.com/o/r/compare/v1.1.0...v1.2.0"
+        );
+        let release = changelog
+            .get_release(
+                &Version::from_str("1.2.0").unwrap(),
+                &package::Name::Default,
+            )
+            .unwrap();
+        assert_eq!(release.title, "1.2.0 (2023-02-01)");
+        assert_eq!(release.notes, "\n## Fixes\n\n- A fix\n");
+        let release = changelog
+            .get_release(
+                &Version::from_str("1.0.0").unwrap(),
+                &package::Name::Default,
+            )
+            .unwrap();
+        assert_eq!(release.notes, "\n- First\n");
+    }
+
+    #[test]
+    fn first_release_of_a_package() {
+        let mut changelog = Changelog::new(
+            RelativePathBuf::from("CHANGELOG.md"),
+            String::from("# Changelog\n\n## [Unreleased]\n\n[Unreleased]: https://github.com/o/r/commits/HEAD\n"),
+        );
+        let _ = changelog.with_release(
+            &release("0.1.0", package::Name::Custom(String::from("first"))),
+            Some(&forge()),
+            &Channels::default(),
+        );
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## [Unreleased]\n\n## [0.1.0] (2023-02-01)\n\n### Fixes\n\n- A fix\n\n[Unreleased]: https://github.com/o/r/compare/first/v0.1.0...HEAD\n[0.1.0]: https://github.com/o/r/releases/tag/first/v0.1.0\n"
+        );
+    }
+
+    #[test]
+    fn no_links_without_definitions() {
+        let content = "# Changelog\n\n## 1.1.0 (2023-01-01)\n\n- Second\n";
+        let mut changelog =
+            Changelog::new(RelativePathBuf::from("CHANGELOG.md"), String::from(content));
+        let _ = changelog.with_release(
+            &release("1.2.0", package::Name::Default),
+            Some(&forge()),
+            &Channels::default(),
+        );
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## 1.2.0 (2023-02-01)\n\n### Fixes\n\n- A fix\n\n## 1.1.0 (2023-01-01)\n\n- Second\n"
+        );
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Error)]
 #[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
 pub enum ParseError {
@@ -3,7 +3,12 @@ use std::{fmt, fmt::Display};
 use git_conventional::FooterToken;
 use serde::{Deserialize, Serialize};
 
-use crate::changes::ChangeType;
+use tracing::debug;
+
+use crate::{
+    changes::{Change, ChangeType},
+    semver::StableRule,
+};
 
 /// Where a custom release section comes from, for example, the custom change type "extra" in
 /// a change file might correspond to a section called "Extras" in the changelog.
@@ -93,7 +98,11 @@ impl From<CustomChangeType> for String {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Sections(pub Vec<(SectionName, Vec<ChangeType>)>);
+pub struct Sections {
+    pub sections: Vec<(SectionName, Vec<ChangeType>)>,
+    /// How much custom change types bump the version, if not [`BumpLevel::Patch`].
+    pub bumps: Vec<(SectionSource, BumpLevel)>,
+}
 
 impl Sections {
     #[must_use]
@@ -107,28 +116,33 @@ impl Sections {
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &(SectionName, Vec<ChangeType>)> {
-        self.0.iter()
+        self.sections.iter()
     }
 
     #[allow(dead_code)]
     pub(crate) fn is_default(&self) -> bool {
         let defaults = Self::defaults();
-        self.0.iter().enumerate().all(|(index, (name, sources))| {
-            if sources.len() != 1 {
-                return false;
-            }
-            sources.first().is_some_and(|source| {
-                defaults
-                    .get(index)
-                    .is_some_and(|(default_name, default_source)| {
-                        name == default_name && source == default_source
+        self.bumps.is_empty()
+            && self
+                .sections
+                .iter()
+                .enumerate()
+                .all(|(index, (name, sources))| {
+                    if sources.len() != 1 {
+                        return false;
+                    }
+                    sources.first().is_some_and(|source| {
+                        defaults
+                            .get(index)
+                            .is_some_and(|(default_name, default_source)| {
+                                name == default_name && source == default_source
+                            })
                     })
-            })
-        })
+                })
     }
 
     pub(crate) fn contains_footer(&self, footer: &git_conventional::Footer) -> bool {
-        self.0.iter().any(|(_, sources)| {
+        self.sections.iter().any(|(_, sources)| {
             sources.iter().any(|source| match source {
                 ChangeType::Custom(SectionSource::CommitFooter(footer_token)) => {
                     footer_token.0.eq_ignore_ascii_case(footer.token().as_str())
@@ -137,16 +151,72 @@ impl Sections {
             })
         })
     }
+
+    /// Find the custom change type matching a conventional commit type, if any section uses it.
+    pub(crate) fn custom_commit_type(&self, commit_type: &str) -> Option<&CustomChangeType> {
+        self.sections.iter().find_map(|(_, sources)| {
+            sources.iter().find_map(|source| match source {
+                ChangeType::Custom(SectionSource::CustomChangeType(change_type))
+                    if change_type.0.eq_ignore_ascii_case(commit_type) =>
+                {
+                    Some(change_type)
+                }
+                _ => None,
+            })
+        })
+    }
+
+    /// How much a type of change bumps the version.
+    #[must_use]
+    pub fn bump_level(&self, change_type: &ChangeType) -> BumpLevel {
+        match change_type {
+            ChangeType::Breaking => BumpLevel::Major,
+            ChangeType::Feature => BumpLevel::Minor,
+            ChangeType::Fix => BumpLevel::Patch,
+            ChangeType::Custom(source) => self
+                .bumps
+                .iter()
+                .find(|(custom, _)| custom == source)
+                .map_or(BumpLevel::Patch, |(_, bump)| *bump),
+        }
+    }
+
+    /// The rule to apply for a set of changes, or `None` if none of them bump the version.
+    #[must_use]
+    pub fn stable_rule<'a>(
+        &self,
+        changes: impl IntoIterator<Item = &'a Change>,
+    ) -> Option<StableRule> {
+        changes
+            .into_iter()
+            .filter_map(|change| {
+                let rule = self.bump_level(&change.change_type).rule();
+                if let Some(rule) = rule {
+                    debug!(
+                        "{change_source}\n\timplies rule {rule}",
+                        change_source = change.original_source
+                    );
+                } else {
+                    debug!(
+                        "{change_source}\n\tdoes not bump the version",
+                        change_source = change.original_source
+                    );
+                }
+                rule
+            })
+            .max()
+    }
 }
 
 impl Default for Sections {
     fn default() -> Self {
-        Self(
-            Self::defaults()
+        Self {
+            sections: Self::defaults()
                 .into_iter()
                 .map(|(name, source)| (name, vec![source]))
                 .collect(),
-        )
+            bumps: Vec::new(),
+        }
     }
 }
 
@@ -155,7 +225,31 @@ impl IntoIterator for Sections {
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.sections.into_iter()
+    }
+}
+
+/// How much a custom change bumps the version of a package.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BumpLevel {
+    Major,
+    Minor,
+    #[default]
+    Patch,
+    /// The change is included in release notes, but doesn't cause a release by itself.
+    None,
+}
+
+impl BumpLevel {
+    #[must_use]
+    pub const fn rule(self) -> Option<StableRule> {
+        match self {
+            Self::Major => Some(StableRule::Major),
+            Self::Minor => Some(StableRule::Minor),
+            Self::Patch => Some(StableRule::Patch),
+            Self::None => None,
+        }
     }
 }
 
@@ -180,3 +274,49 @@ impl AsRef<str> for SectionName {
         &self.0
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::changes::{conventional_commit::CommitSource, ChangeSource};
+
+    fn perf_sections() -> Sections {
+        let perf = SectionSource::CustomChangeType("perf".into());
+        let mut sections = Sections::default();
+        sections
+            .sections
+            .push(("Performance".into(), vec![ChangeType::Custom(perf.clone())]));
+        sections.bumps.push((perf, BumpLevel::Minor));
+        sections
+    }
+
+    #[test]
+    fn is_default_compares_bumps() {
+        assert!(Sections::default().is_default());
+        let mut sections = Sections::default();
+        sections.bumps.push((
+            CommitFooter::from("Changelog-Note").into(),
+            BumpLevel::Minor,
+        ));
+        assert!(!sections.is_default());
+    }
+
+    #[test]
+    fn configured_bumps() {
+        let changes = [Change {
+            change_type: ChangeType::Custom(SectionSource::CustomChangeType("perf".into())),
+            description: "faster".into(),
+            original_source: ChangeSource::ConventionalCommit(CommitSource::from("perf: faster")),
+            skip_release_notes: false,
+            issues: Vec::new(),
+        }];
+        assert_eq!(
+            perf_sections().stable_rule(&changes),
+            Some(StableRule::Minor)
+        );
+        assert_eq!(StableRule::from(&changes), StableRule::Patch);
+    }
+}
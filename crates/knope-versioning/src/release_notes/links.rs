@@ -0,0 +1,321 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::changes::{conventional_commit::CommitSource, ChangeSource, IssueReference};
+
+/// The forge hosting the repository, used to link to commits and pull requests.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Forge {
+    GitHub {
+        owner: String,
+        repo: String,
+    },
+    Gitea {
+        /// Including the scheme, like `https://codeberg.org`
+        host: String,
+        owner: String,
+        repo: String,
+    },
+}
+
+impl Forge {
+    #[must_use]
+    pub fn repository_url(&self) -> String {
+        match self {
+            Self::GitHub { owner, repo } => format!("https://github.com/{owner}/{repo}"),
+            Self::Gitea { host, owner, repo } => {
+                format!("{host}/{owner}/{repo}", host = host.trim_end_matches('/'))
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn commit_url(&self, hash: &str) -> String {
+        format!("{}/commit/{hash}", self.repository_url())
+    }
+
+    #[must_use]
+    pub fn issue_url(&self, number: u64) -> String {
+        format!("{}/issues/{number}", self.repository_url())
+    }
+
+    /// Where to see the changes between two tags (or other revisions).
+    #[must_use]
+    pub fn compare_url(&self, from: &str, to: &str) -> String {
+        format!("{}/compare/{from}...{to}", self.repository_url())
+    }
+
+    #[must_use]
+    pub fn release_url(&self, tag: &str) -> String {
+        format!("{}/releases/tag/{tag}", self.repository_url())
+    }
+
+    #[must_use]
+    pub fn pull_request_url(&self, number: u64) -> String {
+        match self {
+            Self::GitHub { .. } => format!("{}/pull/{number}", self.repository_url()),
+            Self::Gitea { .. } => format!("{}/pulls/{number}", self.repository_url()),
+        }
+    }
+}
+
+/// A Jira project that changes can refer to issues in, like `ABC-42`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Jira {
+    /// The URL of the Atlassian instance, like `https://knope.atlassian.net`
+    pub url: String,
+    /// The key of the project, the prefix of all its issue keys
+    pub project: String,
+}
+
+impl Jira {
+    #[must_use]
+    pub fn issue_url(&self, key: &str) -> String {
+        format!("{}/browse/{key}", self.url.trim_end_matches('/'))
+    }
+}
+
+/// Add the issues that a change refers to the end of `summary`, linked if possible.
+pub(super) fn with_issues(
+    summary: &str,
+    issues: &[IssueReference],
+    forge: Option<&Forge>,
+    jira: Option<&Jira>,
+) -> String {
+    if issues.is_empty() {
+        return summary.to_string();
+    }
+    let links = issues
+        .iter()
+        .map(|issue| {
+            issue_url(issue, forge, jira)
+                .map_or_else(|| issue.to_string(), |url| format!("[{issue}]({url})"))
+        })
+        .join(", ");
+    format!("{summary} ({links})")
+}
+
+/// Where `issue` is, if it's on the forge or a configured Jira project.
+pub(super) fn issue_url(
+    issue: &IssueReference,
+    forge: Option<&Forge>,
+    jira: Option<&Jira>,
+) -> Option<String> {
+    match issue {
+        IssueReference::Number(number) => forge.map(|forge| forge.issue_url(*number)),
+        IssueReference::Jira(key) => jira.map(|jira| jira.issue_url(key)),
+    }
+}
+
+/// Which details of the source commit to add to each change in release notes.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CommitLinks {
+    /// Link to the commit using its abbreviated hash
+    #[serde(default = "enabled")]
+    pub hash: bool,
+    /// Credit the author of the commit
+    #[serde(default = "enabled")]
+    pub author: bool,
+    /// Link to the pull request from a `(#123)` at the end of the commit summary
+    #[serde(default = "enabled")]
+    pub pull_request: bool,
+}
+
+const fn enabled() -> bool {
+    true
+}
+
+impl Default for CommitLinks {
+    fn default() -> Self {
+        Self {
+            hash: true,
+            author: true,
+            pull_request: true,
+        }
+    }
+}
+
+impl CommitLinks {
+    /// Add links to the end of `summary` for the commit or change file that a change came from.
+    ///
+    /// Change files have no hash, but their `authors` and `pr` metadata are added like a commit's.
+    pub(super) fn apply(
+        self,
+        summary: &str,
+        source: &ChangeSource,
+        forge: Option<&Forge>,
+    ) -> String {
+        let (hash, authors, pull_request) = match source {
+            ChangeSource::ConventionalCommit(CommitSource {
+                hash,
+                author,
+                pull_request,
+                ..
+            }) => (
+                hash.as_deref(),
+                author.as_ref().map_or(&[][..], std::slice::from_ref),
+                *pull_request,
+            ),
+            ChangeSource::ChangeFile {
+                authors,
+                pull_request,
+                ..
+            } => (None, authors.as_slice(), *pull_request),
+        };
+        let mut summary = summary.to_string();
+        let pull_request = pull_request.filter(|_| self.pull_request);
+        if let Some(number) = pull_request {
+            if let Some(stripped) = summary.trim_end().strip_suffix(&format!("(#{number})")) {
+                summary = stripped.trim_end().to_string();
+            }
+        }
+        if let Some(hash) = hash.filter(|_| self.hash) {
+            let short = hash.get(..7).unwrap_or(hash);
+            match forge {
+                Some(forge) => {
+                    summary.push_str(&format!(" ([{short}]({}))", forge.commit_url(hash)));
+                }
+                None => summary.push_str(&format!(" ({short})")),
+            }
+        }
+        if self.author && !authors.is_empty() {
+            let authors = authors
+                .iter()
+                .map(|author| match author.username() {
+                    Some(username) => format!("@{username}"),
+                    None => author.name.clone(),
+                })
+                .join(", ");
+            summary.push_str(&format!(" by {authors}"));
+        }
+        if let Some(number) = pull_request {
+            match forge {
+                Some(forge) => summary.push_str(&format!(
+                    " in [#{number}]({})",
+                    forge.pull_request_url(number)
+                )),
+                None => summary.push_str(&format!(" in #{number}")),
+            }
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::changes::conventional_commit::Author;
+
+    fn source() -> ChangeSource {
+        ChangeSource::ConventionalCommit(CommitSource {
+            summary: String::from("fix: Fix a thing (#123)"),
+            hash: Some(String::from("abc1234def5678")),
+            author: Some(Author {
+                name: String::from("Alice"),
+                email: String::from("1234+alice@users.noreply.github.com"),
+                login: None,
+            }),
+            co_authors: Vec::new(),
+            pull_request: Some(123),
+        })
+    }
+
+    #[test]
+    fn github() {
+        let forge = Forge::GitHub {
+            owner: String::from("knope-dev"),
+            repo: String::from("knope"),
+        };
+        assert_eq!(
+            CommitLinks::default().apply("Fix a thing (#123)", &source(), Some(&forge)),
+            "Fix a thing ([abc1234](https://github.com/knope-dev/knope/commit/abc1234def5678)) by @alice in [#123](https://github.com/knope-dev/knope/pull/123)"
+        );
+    }
+
+    #[test]
+    fn gitea() {
+        let forge = Forge::Gitea {
+            host: String::from("https://codeberg.org"),
+            owner: String::from("knope-dev"),
+            repo: String::from("knope"),
+        };
+        assert_eq!(
+            CommitLinks {
+                hash: false,
+                author: false,
+                pull_request: true,
+            }
+            .apply("Fix a thing (#123)", &source(), Some(&forge)),
+            "Fix a thing in [#123](https://codeberg.org/knope-dev/knope/pulls/123)"
+        );
+    }
+
+    #[test]
+    fn issues() {
+        let forge = Forge::GitHub {
+            owner: String::from("knope-dev"),
+            repo: String::from("knope"),
+        };
+        let jira = Jira {
+            url: String::from("https://knope.atlassian.net/"),
+            project: String::from("ABC"),
+        };
+        let issues = [
+            IssueReference::Number(123),
+            IssueReference::Jira(String::from("ABC-42")),
+        ];
+        assert_eq!(
+            with_issues("Fix a thing", &issues, Some(&forge), Some(&jira)),
+            "Fix a thing ([#123](https://github.com/knope-dev/knope/issues/123), [ABC-42](https://knope.atlassian.net/browse/ABC-42))"
+        );
+        assert_eq!(
+            with_issues("Fix a thing", &issues, None, None),
+            "Fix a thing (#123, ABC-42)"
+        );
+    }
+
+    #[test]
+    fn no_forge() {
+        assert_eq!(
+            CommitLinks {
+                hash: true,
+                author: true,
+                pull_request: false,
+            }
+            .apply("Fix a thing (#123)", &source(), None),
+            "Fix a thing (#123) (abc1234) by @alice"
+        );
+    }
+
+    #[test]
+    fn change_file() {
+        let forge = Forge::GitHub {
+            owner: String::from("knope-dev"),
+            repo: String::from("knope"),
+        };
+        let source = ChangeSource::ChangeFile {
+            id: std::sync::Arc::new(changesets::UniqueId::from("Fix a thing")),
+            path: relative_path::RelativePathBuf::from(".changeset/fix_a_thing.md"),
+            author: None,
+            authors: vec![
+                Author {
+                    name: String::from("alice"),
+                    email: String::new(),
+                    login: Some(String::from("alice")),
+                },
+                Author {
+                    name: String::from("Bob"),
+                    email: String::from("bob@example.com"),
+                    login: None,
+                },
+            ],
+            pull_request: Some(456),
+        };
+        assert_eq!(
+            CommitLinks::default().apply("Fix a thing", &source, Some(&forge)),
+            "Fix a thing by @alice, Bob in [#456](https://github.com/knope-dev/knope/pull/456)"
+        );
+    }
+}
@@ -1,22 +1,45 @@
 use std::cmp::Ordering;
 
 pub use changelog::Changelog;
-pub use config::{CommitFooter, CustomChangeType, SectionName, SectionSource, Sections};
+pub use config::{BumpLevel, CommitFooter, CustomChangeType, SectionName, SectionSource, Sections};
 use itertools::Itertools;
+pub use links::{CommitLinks, Forge, Jira};
+use minijinja::Value;
 pub use release::Release;
-use time::{macros::format_description, OffsetDateTime};
+pub use template::{Template, TemplateError, Templates};
+use time::{macros::format_description, Date, OffsetDateTime};
 
-use crate::{changes::Change, package, semver::Version, Action};
+use crate::{
+    changes::{
+        conventional_commit::{Author, CommitSource},
+        Change, ChangeSource, ChangeType, IssueReference,
+    },
+    package,
+    semver::{Channels, Version},
+    Action,
+};
 
 mod changelog;
 mod config;
+mod links;
 mod release;
+mod template;
 
 /// Defines how release notes are handled for a package.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ReleaseNotes {
     pub sections: Sections,
     pub changelog: Option<Changelog>,
+    /// Where the repository is hosted, for links in release notes
+    pub forge: Option<Forge>,
+    /// The Jira project that changes can refer to issues in
+    pub jira: Option<Jira>,
+    /// If set, link each change to the commit it came from
+    pub commit_links: Option<CommitLinks>,
+    /// Whether to thank everyone who authored the changes in a "Contributors" section
+    pub contributors: bool,
+    /// Lay out release notes with these instead of the default
+    pub templates: Templates,
 }
 
 impl ReleaseNotes {
@@ -24,45 +47,83 @@ impl ReleaseNotes {
     ///
     /// # Errors
     ///
-    /// If the current date can't be formatted
+    /// If the current date can't be formatted or a template can't be rendered
     pub fn create_release(
         &mut self,
         version: Version,
         changes: &[Change],
         package_name: &package::Name,
-    ) -> Result<Vec<Action>, TimeError> {
-        let mut notes = String::new();
-        for (section_name, sources) in self.sections.iter() {
-            let changes = changes
-                .iter()
-                .filter_map(|change| {
-                    if sources.contains(&change.change_type) {
-                        Some(ChangeDescription::from(change))
-                    } else {
-                        None
-                    }
-                })
-                .sorted()
-                .collect_vec();
-            if !changes.is_empty() {
-                notes.push_str("\n\n## ");
-                notes.push_str(section_name.as_ref());
-                notes.push_str("\n\n");
-                notes.push_str(&build_body(changes));
-            }
+        channels: &Channels,
+    ) -> Result<Vec<Action>, Error> {
+        let (mut release, changelog_release) = self.releases(
+            version,
+            changes,
+            package_name,
+            OffsetDateTime::now_utc().date(),
+        )?;
+        // The changelog merges its own "Unreleased" section, the forge release needs it too
+        if let Some(unreleased) = self.changelog.as_ref().and_then(Changelog::unreleased) {
+            release.notes = changelog::merge_notes(&release.notes, &unreleased);
         }
 
-        let notes = notes.trim().to_string();
-        let release = Release {
-            title: release_title(&version)?,
-            version,
-            notes,
-            package_name: package_name.clone(),
+        let mut pending_actions = Vec::with_capacity(2);
+        if let Some(changelog) = self.changelog.as_mut() {
+            let new_changes =
+                changelog.with_release(&changelog_release, self.forge.as_ref(), channels);
+            pending_actions.push(Action::WriteToFile {
+                path: changelog.path.clone(),
+                content: changelog.content.clone(),
+                diff: format!("\n{new_changes}\n"),
+            });
         };
+        pending_actions.push(Action::CreateRelease(release));
+        Ok(pending_actions)
+    }
+
+    /// Create release notes for a version which was already released on `date`, like when
+    /// filling in a changelog from old tags.
+    ///
+    /// If the changelog already has this version, its notes are reused and it isn't changed.
+    /// Otherwise, the release is inserted in order of version, using `channels` for pre-releases.
+    /// Releases with no notes aren't added to the changelog.
+    ///
+    /// # Errors
+    ///
+    /// If `date` can't be formatted or a template can't be rendered
+    pub fn create_past_release(
+        &mut self,
+        version: Version,
+        changes: &[Change],
+        package_name: &package::Name,
+        date: Date,
+        channels: &Channels,
+    ) -> Result<Vec<Action>, Error> {
+        if let Some(existing) = self
+            .changelog
+            .as_ref()
+            .filter(|changelog| changelog.has_release(&version))
+            .map(|changelog| {
+                changelog
+                    .get_release(&version, package_name)
+                    .unwrap_or_else(|| Release {
+                        title: version.to_string(),
+                        version: version.clone(),
+                        notes: String::new(),
+                        package_name: package_name.clone(),
+                    })
+            })
+        {
+            return Ok(vec![Action::CreateRelease(existing)]);
+        }
+        let (release, changelog_release) = self.releases(version, changes, package_name, date)?;
 
         let mut pending_actions = Vec::with_capacity(2);
-        if let Some(changelog) = self.changelog.as_mut() {
-            let new_changes = changelog.with_release(&release);
+        if let Some(changelog) = self
+            .changelog
+            .as_mut()
+            .filter(|_| !changelog_release.notes.is_empty())
+        {
+            let new_changes = changelog.with_past_release(&changelog_release, channels);
             pending_actions.push(Action::WriteToFile {
                 path: changelog.path.clone(),
                 content: changelog.content.clone(),
@@ -72,6 +133,277 @@ impl ReleaseNotes {
         pending_actions.push(Action::CreateRelease(release));
         Ok(pending_actions)
     }
+
+    /// Update the "Unreleased" section of the changelog with notes for `changes`, which haven't
+    /// been released yet. Returns `None` if there is no changelog.
+    ///
+    /// # Errors
+    ///
+    /// If the current date can't be formatted or a template can't be rendered
+    pub fn update_unreleased(
+        &mut self,
+        changes: &[Change],
+        package_name: &package::Name,
+    ) -> Result<Option<Action>, Error> {
+        if self.changelog.is_none() {
+            return Ok(None);
+        }
+        let date = format_date(OffsetDateTime::now_utc().date())?;
+        let heading = Heading {
+            version: String::from("Unreleased"),
+            title: "Unreleased",
+            date: &date,
+            package_name,
+        };
+        let sections = self.sections(changes);
+        let notes = self.notes(
+            self.templates.changelog.as_ref(),
+            &heading,
+            &sections,
+            changes,
+        )?;
+        Ok(self.changelog.as_mut().map(|changelog| {
+            let section = changelog.with_unreleased(&notes);
+            Action::WriteToFile {
+                path: changelog.path.clone(),
+                content: changelog.content.clone(),
+                diff: format!("\n{section}\n"),
+            }
+        }))
+    }
+
+    /// The release to create on a forge and the one to add to the changelog, which only differ
+    /// when there's a separate template for each.
+    fn releases(
+        &self,
+        version: Version,
+        changes: &[Change],
+        package_name: &package::Name,
+        date: Date,
+    ) -> Result<(Release, Release), Error> {
+        let date = format_date(date)?;
+        let title = format!("{version} ({date})");
+        let heading = Heading {
+            version: version.to_string(),
+            title: &title,
+            date: &date,
+            package_name,
+        };
+        let sections = self.sections(changes);
+        let notes = self.notes(self.templates.for_release(), &heading, &sections, changes)?;
+        let changelog_notes = if self.templates.release.is_some() {
+            self.notes(
+                self.templates.changelog.as_ref(),
+                &heading,
+                &sections,
+                changes,
+            )?
+        } else {
+            notes.clone()
+        };
+        let release = Release {
+            title,
+            version,
+            notes,
+            package_name: package_name.clone(),
+        };
+        let changelog_release = Release {
+            notes: changelog_notes,
+            ..release.clone()
+        };
+        Ok((release, changelog_release))
+    }
+
+    /// The changes in each section that has any, as they're listed in release notes.
+    fn sections<'a>(&'a self, changes: &'a [Change]) -> Vec<Section<'a>> {
+        self.sections
+            .iter()
+            .filter_map(|(name, sources)| {
+                let changes = changes
+                    .iter()
+                    .filter(|change| {
+                        !change.skip_release_notes && sources.contains(&change.change_type)
+                    })
+                    .map(|change| (change, self.describe(change)))
+                    .sorted_by(|(_, first), (_, second)| first.cmp(second))
+                    .collect_vec();
+                (!changes.is_empty()).then_some(Section { name, changes })
+            })
+            .collect()
+    }
+
+    /// How `change` is described in release notes, with links to its issues and source.
+    fn describe(&self, change: &Change) -> ChangeDescription {
+        let description = ChangeDescription::from(change).with_issues(
+            &change.issues,
+            self.forge.as_ref(),
+            self.jira.as_ref(),
+        );
+        match self.commit_links {
+            Some(links) => description.with_links(links, change, self.forge.as_ref()),
+            None => description,
+        }
+    }
+
+    /// The Markdown notes under `heading`, at header level 1, from `template` or the default layout.
+    fn notes(
+        &self,
+        template: Option<&Template>,
+        heading: &Heading,
+        sections: &[Section],
+        changes: &[Change],
+    ) -> Result<String, TemplateError> {
+        let Some(template) = template else {
+            return Ok(self.default_notes(sections, changes));
+        };
+        let context = vec![
+            ("version", Value::from(heading.version.as_str())),
+            ("date", Value::from(heading.date)),
+            ("title", Value::from(heading.title)),
+            ("package", Value::from(heading.package_name.as_custom())),
+            (
+                "sections",
+                sections
+                    .iter()
+                    .map(|section| {
+                        Value::from_iter([
+                            ("name", Value::from(section.name.as_ref())),
+                            ("changes", self.change_values(&section.changes)),
+                        ])
+                    })
+                    .collect(),
+            ),
+            (
+                "changes",
+                self.change_values(
+                    &sections
+                        .iter()
+                        .flat_map(|section| section.changes.iter().cloned())
+                        .collect_vec(),
+                ),
+            ),
+            (
+                "contributors",
+                unique_authors(changes).map(author_value).collect(),
+            ),
+        ];
+        Ok(template.render(context)?.trim().to_string())
+    }
+
+    fn default_notes(&self, sections: &[Section], changes: &[Change]) -> String {
+        let mut notes = String::new();
+        for section in sections {
+            notes.push_str("\n\n## ");
+            notes.push_str(section.name.as_ref());
+            notes.push_str("\n\n");
+            notes.push_str(&build_body(
+                section.changes.iter().map(|(_, description)| description),
+            ));
+        }
+
+        if self.contributors {
+            let contributors = contributors(changes);
+            if !contributors.is_empty() {
+                notes.push_str("\n\n## Contributors\n\n");
+                notes.push_str(&contributors.join("\n"));
+            }
+        }
+
+        notes.trim().to_string()
+    }
+
+    /// The details of each change that templates can use.
+    fn change_values(&self, changes: &[(&Change, ChangeDescription)]) -> Value {
+        changes
+            .iter()
+            .map(|(change, description)| self.change_value(change, description))
+            .collect()
+    }
+
+    fn change_value(&self, change: &Change, description: &ChangeDescription) -> Value {
+        let forge = self.forge.as_ref();
+        let (summary, body) = match ChangeDescription::from(change) {
+            ChangeDescription::Simple(summary) => (summary, None),
+            ChangeDescription::Complex(summary, body) => (summary, Some(body)),
+        };
+        let (ChangeDescription::Simple(linked_summary)
+        | ChangeDescription::Complex(linked_summary, _)) = description;
+        let (hash, pull_request, source) = match &change.original_source {
+            ChangeSource::ConventionalCommit(CommitSource {
+                hash, pull_request, ..
+            }) => (hash.as_deref(), *pull_request, "commit"),
+            ChangeSource::ChangeFile { pull_request, .. } => (None, *pull_request, "change_file"),
+        };
+        let issues: Value = change
+            .issues
+            .iter()
+            .map(|issue| {
+                Value::from_iter([
+                    ("name", Value::from(issue.to_string())),
+                    (
+                        "url",
+                        Value::from(links::issue_url(issue, forge, self.jira.as_ref())),
+                    ),
+                ])
+            })
+            .collect();
+        let pull_request = pull_request.map(|number| {
+            Value::from_iter([
+                ("number", Value::from(number)),
+                (
+                    "url",
+                    Value::from(forge.map(|forge| forge.pull_request_url(number))),
+                ),
+            ])
+        });
+        let commit = hash.map(|hash| {
+            Value::from_iter([
+                ("hash", Value::from(hash)),
+                ("short", Value::from(hash.get(..7).unwrap_or(hash))),
+                (
+                    "url",
+                    Value::from(forge.map(|forge| forge.commit_url(hash))),
+                ),
+            ])
+        });
+        Value::from_iter([
+            ("summary", Value::from(summary)),
+            ("body", Value::from(body)),
+            ("linked_summary", Value::from(linked_summary.as_str())),
+            ("type", Value::from(change_type_name(&change.change_type))),
+            ("source", Value::from(source)),
+            ("issues", issues),
+            ("pull_request", Value::from(pull_request)),
+            ("commit", Value::from(commit)),
+            ("authors", change.authors().map(author_value).collect()),
+        ])
+    }
+}
+
+/// What release notes are for, as templates see it.
+struct Heading<'a> {
+    /// The version, or `Unreleased`
+    version: String,
+    title: &'a str,
+    date: &'a str,
+    package_name: &'a package::Name,
+}
+
+/// The changes in one section of release notes, each with how it's described.
+struct Section<'a> {
+    name: &'a SectionName,
+    changes: Vec<(&'a Change, ChangeDescription)>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum Error {
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Time(#[from] TimeError),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Template(#[from] TemplateError),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -105,6 +437,35 @@ impl PartialOrd for ChangeDescription {
     }
 }
 
+impl ChangeDescription {
+    /// Add the issues that the change refers to after its summary.
+    fn with_issues(
+        self,
+        issues: &[IssueReference],
+        forge: Option<&Forge>,
+        jira: Option<&Jira>,
+    ) -> Self {
+        match self {
+            Self::Simple(summary) => {
+                Self::Simple(links::with_issues(&summary, issues, forge, jira))
+            }
+            Self::Complex(summary, body) => {
+                Self::Complex(links::with_issues(&summary, issues, forge, jira), body)
+            }
+        }
+    }
+
+    /// Add links to the source of the change, only for single-line changes.
+    fn with_links(self, links: CommitLinks, change: &Change, forge: Option<&Forge>) -> Self {
+        match self {
+            Self::Simple(summary) => {
+                Self::Simple(links.apply(&summary, &change.original_source, forge))
+            }
+            complex @ Self::Complex(..) => complex,
+        }
+    }
+}
+
 impl From<&Change> for ChangeDescription {
     fn from(change: &Change) -> Self {
         let mut lines = change
@@ -127,9 +488,58 @@ impl From<&Change> for ChangeDescription {
     }
 }
 
-fn build_body(changes: Vec<ChangeDescription>) -> String {
+/// The unique authors of `changes` as Markdown list items, sorted by name.
+///
+/// Authors are the same person if they have the same username or email (ignoring case).
+/// Those without a known username are listed by name and email.
+fn contributors(changes: &[Change]) -> Vec<String> {
+    unique_authors(changes)
+        .map(|author| format!("- {}", contributor_name(author)))
+        .collect()
+}
+
+/// Everyone who authored `changes`, each once, sorted the way they're listed as contributors.
+fn unique_authors(changes: &[Change]) -> impl Iterator<Item = &Author> {
+    changes
+        .iter()
+        .flat_map(Change::authors)
+        .unique_by(|author| {
+            author.username().map_or_else(
+                || author.email.to_lowercase(),
+                |username| format!("@{}", username.to_lowercase()),
+            )
+        })
+        .sorted_by_key(|author| contributor_name(author).to_lowercase())
+}
+
+fn contributor_name(author: &Author) -> String {
+    match author.username() {
+        Some(username) => format!("@{username}"),
+        None => format!("{} <{}>", author.name, author.email),
+    }
+}
+
+fn author_value(author: &Author) -> Value {
+    Value::from_iter([
+        ("name", Value::from(author.name.as_str())),
+        ("email", Value::from(author.email.as_str())),
+        ("login", Value::from(author.username())),
+    ])
+}
+
+/// What templates call a type of change, like `feature` or the name of a custom change type.
+fn change_type_name(change_type: &ChangeType) -> String {
+    match change_type {
+        ChangeType::Breaking => String::from("breaking"),
+        ChangeType::Feature => String::from("feature"),
+        ChangeType::Fix => String::from("fix"),
+        ChangeType::Custom(source) => source.to_string(),
+    }
+}
+
+fn build_body<'a>(changes: impl Iterator<Item = &'a ChangeDescription>) -> String {
     let mut body = String::new();
-    let mut changes = changes.into_iter().peekable();
+    let mut changes = changes.peekable();
     while let Some(change) = changes.next() {
         match change {
             ChangeDescription::Simple(summary) => {
@@ -148,15 +558,14 @@ fn build_body(changes: Vec<ChangeDescription>) -> String {
     body
 }
 
-/// Create the title of a new release with no Markdown header level.
+/// Format the date of a release, like `2024-12-25`.
 ///
 /// # Errors
 ///
-/// If the current date can't be formatted
-fn release_title(version: &Version) -> Result<String, TimeError> {
+/// If the date can't be formatted
+fn format_date(date: Date) -> Result<String, TimeError> {
     let format = format_description!("[year]-[month]-[day]");
-    let date_str = OffsetDateTime::now_utc().date().format(&format)?;
-    Ok(format!("{version} ({date_str})"))
+    Ok(date.format(&format)?)
 }
 
 #[cfg(test)]
@@ -164,14 +573,16 @@ mod test_change_description {
     use pretty_assertions::assert_eq;
 
     use super::*;
-    use crate::changes::{ChangeSource, ChangeType};
+    use crate::changes::{conventional_commit::CommitSource, ChangeSource, ChangeType};
 
     #[test]
     fn conventional_commit() {
         let change = Change {
             change_type: ChangeType::Feature,
-            original_source: ChangeSource::ConventionalCommit(String::new()),
+            original_source: ChangeSource::ConventionalCommit(CommitSource::default()),
             description: "a feature".into(),
+            skip_release_notes: false,
+            issues: Vec::new(),
         };
         let description = ChangeDescription::from(&change);
         assert_eq!(
@@ -184,8 +595,10 @@ mod test_change_description {
     fn simple_changeset() {
         let change = Change {
             change_type: ChangeType::Feature,
-            original_source: ChangeSource::ConventionalCommit(String::new()),
+            original_source: ChangeSource::ConventionalCommit(CommitSource::default()),
             description: "# a feature\n\n\n\n".into(),
+            skip_release_notes: false,
+            issues: Vec::new(),
         };
         let description = ChangeDescription::from(&change);
         assert_eq!(
@@ -197,9 +610,11 @@ mod test_change_description {
     #[test]
     fn complex_changeset() {
         let change = Change {
-            original_source: ChangeSource::ConventionalCommit(String::new()),
+            original_source: ChangeSource::ConventionalCommit(CommitSource::default()),
             change_type: ChangeType::Feature,
             description: "# a feature\n\nwith details\n\n- first\n- second".into(),
+            skip_release_notes: false,
+            issues: Vec::new(),
         };
         let description = ChangeDescription::from(&change);
         assert_eq!(
@@ -211,3 +626,54 @@ mod test_change_description {
         );
     }
 }
+
+#[cfg(test)]
+mod test_contributors {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::changes::{
+        conventional_commit::{Author, CommitSource},
+        ChangeSource, ChangeType,
+    };
+
+    fn author(name: &str, email: &str) -> Author {
+        Author {
+            name: name.to_string(),
+            email: email.to_string(),
+            login: None,
+        }
+    }
+
+    fn change(author: Author, co_authors: Vec<Author>) -> Change {
+        Change {
+            change_type: ChangeType::Fix,
+            description: "a fix".into(),
+            original_source: ChangeSource::ConventionalCommit(CommitSource {
+                author: Some(author),
+                co_authors,
+                ..CommitSource::default()
+            }),
+            skip_release_notes: false,
+            issues: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn unique_and_sorted() {
+        let mut resolved = author("Carol", "carol@example.com");
+        resolved.login = Some(String::from("carol"));
+        let changes = [
+            change(
+                author("Bob", "bob@example.com"),
+                vec![author("Alice", "1234+alice@users.noreply.github.com")],
+            ),
+            change(author("Bob", "BOB@example.com"), vec![resolved]),
+            change(author("Alice", "alice@users.noreply.github.com"), vec![]),
+        ];
+        assert_eq!(
+            contributors(&changes),
+            vec!["- @alice", "- @carol", "- Bob <bob@example.com>"]
+        );
+    }
+}
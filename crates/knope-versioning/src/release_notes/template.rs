@@ -0,0 +1,148 @@
+//! Custom layouts for release notes, written in [Jinja](https://jinja.palletsprojects.com/) and
+//! rendered with [MiniJinja](https://docs.rs/minijinja).
+//!
+//! Block tags (`{% if %}`, `{% for %}`, …) which are alone on a line leave nothing behind in the
+//! output, and using a variable which doesn't exist is an error.
+
+use minijinja::{AutoEscape, Environment, UndefinedBehavior, Value};
+use relative_path::RelativePathBuf;
+
+/// Templates which replace the default layout of release notes.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Templates {
+    /// Renders new releases in the changelog
+    pub changelog: Option<Template>,
+    /// Renders the body of releases on a forge, the changelog template is used if this isn't set
+    pub release: Option<Template>,
+}
+
+impl Templates {
+    pub(super) fn for_release(&self) -> Option<&Template> {
+        self.release.as_ref().or(self.changelog.as_ref())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Template {
+    /// Where the template came from, for errors
+    path: RelativePathBuf,
+    source: String,
+}
+
+impl Template {
+    /// Parse the `source` of a template which came from `path`.
+    ///
+    /// # Errors
+    ///
+    /// If the template has invalid syntax, like an `{% if %}` without an `{% endif %}`.
+    pub fn parse(path: RelativePathBuf, source: &str) -> Result<Self, TemplateError> {
+        environment()
+            .template_from_named_str(path.as_str(), source)
+            .map_err(|err| TemplateError::new(&path, &err))?;
+        Ok(Self {
+            path,
+            source: source.to_string(),
+        })
+    }
+
+    /// Render the template with the variables in `context`.
+    pub(super) fn render(
+        &self,
+        context: Vec<(&'static str, Value)>,
+    ) -> Result<String, TemplateError> {
+        environment()
+            .template_from_named_str(self.path.as_str(), &self.source)
+            .and_then(|template| template.render(Value::from_iter(context)))
+            .map_err(|err| TemplateError::new(&self.path, &err))
+    }
+}
+
+fn environment() -> Environment<'static> {
+    let mut environment = Environment::new();
+    environment.set_trim_blocks(true);
+    environment.set_lstrip_blocks(true);
+    environment.set_undefined_behavior(UndefinedBehavior::Strict);
+    environment.set_auto_escape_callback(|_| AutoEscape::None);
+    environment
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+#[error("Problem with the template {path} on line {line}: {message}")]
+#[cfg_attr(
+    feature = "miette",
+    diagnostic(
+        code(release_notes::template),
+        help(
+            "Templates are rendered with MiniJinja, check the documentation for what's available."
+        ),
+        url("https://knope.tech/reference/config-file/packages/#changelog_template")
+    )
+)]
+pub struct TemplateError {
+    path: RelativePathBuf,
+    line: usize,
+    message: String,
+}
+
+impl TemplateError {
+    fn new(path: &RelativePathBuf, err: &minijinja::Error) -> Self {
+        let message = match err.detail() {
+            Some(detail) => format!("{}: {detail}", err.kind()),
+            None => err.kind().to_string(),
+        };
+        Self {
+            path: path.clone(),
+            line: err.line().unwrap_or_default(),
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn render(source: &str, context: Vec<(&'static str, Value)>) -> Result<String, TemplateError> {
+        Template::parse(RelativePathBuf::from("template.md"), source)?.render(context)
+    }
+
+    #[test]
+    fn block_lines_leave_nothing_behind() {
+        let template = "\
+{% for change in changes %}
+  {% if change.issues %}
+- {{ change.summary }} ({{ change.issues | join(\", \") }})
+  {% else %}
+- {{ change.summary }}
+  {% endif %}
+{% endfor %}
+";
+        let changes = Value::from(vec![
+            Value::from_iter([
+                ("summary", Value::from("First")),
+                ("issues", Value::from(vec!["#1", "#2"])),
+            ]),
+            Value::from_iter([
+                ("summary", Value::from("<Second>")),
+                ("issues", Value::from(Vec::<Value>::new())),
+            ]),
+        ]);
+        assert_eq!(
+            render(template, vec![("changes", changes)]).unwrap(),
+            "- First (#1, #2)\n- <Second>\n"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = render("line one\n{% if true %}\nnever closed", Vec::new()).unwrap_err();
+        assert_eq!(error.line, 3);
+        let error = render("\n{{ chnage.summary }}", Vec::new()).unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "undefined value");
+    }
+}
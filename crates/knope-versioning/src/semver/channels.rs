@@ -0,0 +1,108 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Label, Prerelease, Version};
+
+/// The order of pre-release channels (labels), from least to most stable.
+///
+/// Labels which aren't listed are considered less stable than any listed label and are ordered
+/// alphabetically among themselves, so with no channels configured, `alpha` < `beta` < `rc`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Channels(Vec<Label>);
+
+impl Channels {
+    #[must_use]
+    pub fn new(channels: Vec<Label>) -> Self {
+        Self(channels)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Compare two labels by their position in the channel list.
+    #[must_use]
+    pub fn cmp_labels(&self, label: &Label, other: &Label) -> Ordering {
+        let position = |label: &Label| self.0.iter().position(|channel| channel == label);
+        match (position(label), position(other)) {
+            (Some(position), Some(other_position)) => position.cmp(&other_position),
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (None, None) => label.cmp(other),
+        }
+    }
+
+    /// Compare two pre-releases, first by channel, then by their remaining identifiers.
+    #[must_use]
+    pub fn cmp_prereleases(&self, pre: &Prerelease, other: &Prerelease) -> Ordering {
+        if pre.label == other.label {
+            pre.cmp(other)
+        } else {
+            self.cmp_labels(&pre.label, &other.label)
+        }
+    }
+
+    /// Compare the precedence of two versions, using channel order for pre-release labels.
+    ///
+    /// Like [`Version::cmp_precedence`], build metadata is ignored.
+    #[must_use]
+    pub fn cmp_versions(&self, version: &Version, other: &Version) -> Ordering {
+        version
+            .stable_component()
+            .cmp(&other.stable_component())
+            .then_with(|| match (version.pre_component(), other.pre_component()) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(pre), Some(other_pre)) => self.cmp_prereleases(pre, other_pre),
+            })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::str::FromStr;
+
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn default_is_alphabetical() {
+        let channels = Channels::default();
+        assert_eq!(
+            channels.cmp_labels(&"alpha".into(), &"beta".into()),
+            Ordering::Less
+        );
+        assert_eq!(
+            channels.cmp_labels(&"rc".into(), &"preview".into()),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn configured_order() {
+        let channels = Channels::new(vec!["canary".into(), "next".into(), "preview".into()]);
+        let versions = [
+            "1.0.0-alpha.5",
+            "1.0.0-canary.0",
+            "1.0.0-canary.1",
+            "1.0.0-next.0",
+            "1.0.0-preview.0",
+            "1.0.0",
+            "1.1.0-canary.0",
+        ]
+        .map(|version| Version::from_str(version).unwrap());
+        for (lower, higher) in versions.iter().tuple_windows() {
+            assert_eq!(
+                channels.cmp_versions(lower, higher),
+                Ordering::Less,
+                "{lower} < {higher}"
+            );
+        }
+    }
+}
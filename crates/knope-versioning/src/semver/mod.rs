@@ -1,57 +1,87 @@
 use std::{cmp::Ordering, fmt::Display, str::FromStr};
 
+pub use channels::Channels;
 #[cfg(feature = "miette")]
 use miette::Diagnostic;
 pub use package_versions::{PackageVersions, PreReleaseNotFound};
+pub use pep440::Pep440;
 pub use rule::{Rule, Stable as StableRule};
 use serde::{Deserialize, Serialize};
 
+mod channels;
 mod package_versions;
+pub mod pep440;
 mod prerelease_map;
 mod rule;
 
+/// A [Semantic Version](https://semver.org/spec/v2.0.0.html), including optional pre-release
+/// identifiers and build metadata.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub enum Version {
-    Stable(StableVersion),
-    Pre(PreVersion),
+pub struct Version {
+    stable_component: StableVersion,
+    pre_component: Option<Prerelease>,
+    build_metadata: Option<BuildMetadata>,
 }
 
 impl Version {
     #[must_use]
     pub const fn stable_component(&self) -> StableVersion {
-        match self {
-            Self::Stable(stable) => *stable,
-            Self::Pre(pre) => pre.stable_component,
-        }
+        self.stable_component
+    }
+
+    #[must_use]
+    pub const fn pre_component(&self) -> Option<&Prerelease> {
+        self.pre_component.as_ref()
+    }
+
+    #[must_use]
+    pub const fn build_metadata(&self) -> Option<&BuildMetadata> {
+        self.build_metadata.as_ref()
     }
 
     #[must_use]
     pub const fn is_prerelease(&self) -> bool {
-        matches!(self, Version::Pre(_))
+        self.pre_component.is_some()
+    }
+
+    /// Replace the build metadata (the part after `+`) of this version.
+    #[must_use]
+    pub fn with_build_metadata(mut self, build_metadata: Option<BuildMetadata>) -> Self {
+        self.build_metadata = build_metadata;
+        self
     }
 }
 
 impl Version {
     #[must_use]
     pub fn new(major: u64, minor: u64, patch: u64, pre: Option<Prerelease>) -> Self {
-        let stable = StableVersion {
-            major,
-            minor,
-            patch,
-        };
-        match pre {
-            Some(pre) => Self::Pre(PreVersion {
-                stable_component: stable,
-                pre_component: pre,
-            }),
-            None => Self::Stable(stable),
+        Self {
+            stable_component: StableVersion {
+                major,
+                minor,
+                patch,
+            },
+            pre_component: pre,
+            build_metadata: None,
+        }
+    }
+
+    pub(crate) const fn pre(stable_component: StableVersion, pre_component: Prerelease) -> Self {
+        Self {
+            stable_component,
+            pre_component: Some(pre_component),
+            build_metadata: None,
         }
     }
 }
 
 impl From<StableVersion> for Version {
-    fn from(stable: StableVersion) -> Self {
-        Self::Stable(stable)
+    fn from(stable_component: StableVersion) -> Self {
+        Self {
+            stable_component,
+            pre_component: None,
+            build_metadata: None,
+        }
     }
 }
 
@@ -132,25 +162,17 @@ impl Display for StableVersion {
     }
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct PreVersion {
-    pub stable_component: StableVersion,
-    pub pre_component: Prerelease,
-}
-
+/// Versions are ordered by [precedence](https://semver.org/spec/v2.0.0.html#spec-item-11).
+///
+/// That orders pre-release labels alphabetically, so versions of a package should be compared with
+/// [`Channels::cmp_versions`] instead, which follows the package's configured channels.
+///
+/// Build metadata doesn't affect precedence, it's only compared to keep the ordering consistent
+/// with `Eq`.
 impl Ord for Version {
     fn cmp(&self, other: &Self) -> Ordering {
-        match self.stable_component().cmp(&other.stable_component()) {
-            Ordering::Equal => match (self, other) {
-                (Self::Stable(_), Self::Stable(_)) => Ordering::Equal,
-                (Self::Stable(_), Self::Pre(_)) => Ordering::Greater,
-                (Self::Pre(_), Self::Stable(_)) => Ordering::Less,
-                (Self::Pre(pre), Self::Pre(other_pre)) => {
-                    pre.pre_component.cmp(&other_pre.pre_component)
-                }
-            },
-            ordering => ordering,
-        }
+        self.cmp_precedence(other)
+            .then_with(|| self.build_metadata.cmp(&other.build_metadata))
     }
 }
 
@@ -160,33 +182,71 @@ impl PartialOrd for Version {
     }
 }
 
+impl Version {
+    /// Compare two versions while ignoring build metadata, as described by the Semantic
+    /// Versioning spec.
+    #[must_use]
+    pub fn cmp_precedence(&self, other: &Self) -> Ordering {
+        self.stable_component
+            .cmp(&other.stable_component)
+            .then_with(|| match (&self.pre_component, &other.pre_component) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(pre), Some(other_pre)) => pre.cmp(other_pre),
+            })
+    }
+}
+
 impl FromStr for Version {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (s, build_metadata) = s
+            .split_once('+')
+            .map_or((s, None), |(version, build)| (version, Some(build)));
         let (version, pre) = s
             .split_once('-')
             .map_or((s, None), |(version, pre)| (version, Some(pre)));
         let version_parts: [u64; 3] = version
             .split('.')
-            .map(|part| part.parse::<u64>().map_err(|err| Error(err.to_string())))
+            .map(parse_numeric)
             .collect::<Result<Vec<_>, _>>()?
             .try_into()
             .map_err(|_| Error("Version must have exactly 3 parts".to_string()))?;
-        let stable = StableVersion {
-            major: version_parts[0],
-            minor: version_parts[1],
-            patch: version_parts[2],
-        };
-        if let Some(pre) = pre {
-            Ok(Self::Pre(PreVersion {
-                stable_component: stable,
-                pre_component: Prerelease::from_str(pre)?,
-            }))
-        } else {
-            Ok(Self::Stable(stable))
-        }
+        let [major, minor, patch] = version_parts;
+        Ok(Self {
+            stable_component: StableVersion {
+                major,
+                minor,
+                patch,
+            },
+            pre_component: pre.map(Prerelease::from_str).transpose()?,
+            build_metadata: build_metadata.map(BuildMetadata::from_str).transpose()?,
+        })
+    }
+}
+
+/// Parse a numeric identifier, which must be only digits with no leading zeros.
+fn parse_numeric(s: &str) -> Result<u64, Error> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+        return Err(Error(format!("{s} is not a number")));
+    }
+    if s.len() > 1 && s.starts_with('0') {
+        return Err(Error(format!("{s} must not have leading zeros")));
     }
+    s.parse::<u64>().map_err(|err| Error(err.to_string()))
+}
+
+/// Check that an identifier is non-empty and only contains `[0-9A-Za-z-]`.
+fn validate_identifier(s: &str) -> Result<(), Error> {
+    if s.is_empty() {
+        return Err(Error("Identifiers must not be empty".to_string()));
+    }
+    if let Some(c) = s.chars().find(|c| !c.is_ascii_alphanumeric() && *c != '-') {
+        return Err(Error(format!("Invalid character {c} in identifier {s}")));
+    }
+    Ok(())
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -204,25 +264,38 @@ pub struct Error(String);
 
 impl Display for Version {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Stable(stable) => write!(f, "{stable}"),
-            Self::Pre(PreVersion {
-                stable_component,
-                pre_component,
-            }) => write!(f, "{stable_component}-{pre_component}",),
+        write!(f, "{}", self.stable_component)?;
+        if let Some(pre) = &self.pre_component {
+            write!(f, "-{pre}")?;
+        }
+        if let Some(build) = &self.build_metadata {
+            write!(f, "+{build}")?;
         }
+        Ok(())
     }
 }
 
+/// The pre-release component of a version, for example, `rc.1` in `1.0.0-rc.1`.
+///
+/// Knope manages pre-releases formatted like `<label>.<version>`, but any valid identifiers are
+/// accepted: `alpha` has no `version` and `rc.1.hotfix` has `extra` identifiers.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Prerelease {
     pub label: Label,
-    pub version: u64,
+    pub version: Option<u64>,
+    pub extra: Vec<Identifier>,
 }
 
 impl Display for Prerelease {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}.{}", self.label, self.version)
+        write!(f, "{}", self.label)?;
+        if let Some(version) = self.version {
+            write!(f, ".{version}")?;
+        }
+        for identifier in &self.extra {
+            write!(f, ".{identifier}")?;
+        }
+        Ok(())
     }
 }
 
@@ -230,23 +303,38 @@ impl FromStr for Prerelease {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (label, version) = s
-            .split_once('.')
+        let mut identifiers = s
+            .split('.')
+            .map(Identifier::from_str)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .peekable();
+        let label = identifiers
+            .next()
+            .map(|label| Label(label.to_string()))
             .ok_or_else(|| Error("Invalid prerelease".to_string()))?;
+        let version = identifiers
+            .next_if(Identifier::is_numeric)
+            .and_then(|identifier| {
+                if let Identifier::Numeric(version) = identifier {
+                    Some(version)
+                } else {
+                    None
+                }
+            });
         Ok(Self {
-            label: Label(String::from(label)),
-            version: version
-                .parse::<u64>()
-                .map_err(|err| Error(err.to_string()))?,
+            label,
+            version,
+            extra: identifiers.collect(),
         })
     }
 }
 
 impl Ord for Prerelease {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.label
-            .cmp(&other.label)
-            .then(self.version.cmp(&other.version))
+        // Comparing the whole list of identifiers matches the precedence rules of the spec:
+        // pairwise, then a longer list has higher precedence.
+        self.identifiers().cmp(other.identifiers())
     }
 }
 
@@ -259,7 +347,113 @@ impl PartialOrd for Prerelease {
 impl Prerelease {
     #[must_use]
     pub fn new(label: Label, version: u64) -> Self {
-        Self { label, version }
+        Self {
+            label,
+            version: Some(version),
+            extra: Vec::new(),
+        }
+    }
+
+    fn identifiers(&self) -> impl Iterator<Item = Identifier> + '_ {
+        std::iter::once(Identifier::from(self.label.0.as_str()))
+            .chain(self.version.map(Identifier::Numeric))
+            .chain(self.extra.iter().cloned())
+    }
+}
+
+/// A single dot-separated part of a pre-release.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Identifier {
+    Numeric(u64),
+    AlphaNumeric(String),
+}
+
+impl Identifier {
+    const fn is_numeric(&self) -> bool {
+        matches!(self, Self::Numeric(_))
+    }
+}
+
+impl FromStr for Identifier {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        validate_identifier(s)?;
+        if s.chars().all(|c| c.is_ascii_digit()) {
+            parse_numeric(s).map(Self::Numeric)
+        } else {
+            Ok(Self::AlphaNumeric(s.to_string()))
+        }
+    }
+}
+
+/// Used for labels, which can't fail to parse but might be numbers.
+impl From<&str> for Identifier {
+    fn from(s: &str) -> Self {
+        Self::from_str(s).unwrap_or_else(|_| Self::AlphaNumeric(s.to_string()))
+    }
+}
+
+impl Display for Identifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Numeric(number) => write!(f, "{number}"),
+            Self::AlphaNumeric(string) => write!(f, "{string}"),
+        }
+    }
+}
+
+/// Numeric identifiers always have lower precedence than alphanumeric ones.
+impl Ord for Identifier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Numeric(a), Self::Numeric(b)) => a.cmp(b),
+            (Self::Numeric(_), Self::AlphaNumeric(_)) => Ordering::Less,
+            (Self::AlphaNumeric(_), Self::Numeric(_)) => Ordering::Greater,
+            (Self::AlphaNumeric(a), Self::AlphaNumeric(b)) => a.cmp(b),
+        }
+    }
+}
+
+impl PartialOrd for Identifier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The build metadata of a version, for example, `build.45` in `1.2.3+build.45`.
+///
+/// This is never used when determining the order of versions.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct BuildMetadata(String);
+
+impl FromStr for BuildMetadata {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        for identifier in s.split('.') {
+            validate_identifier(identifier)?;
+        }
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl Display for BuildMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for BuildMetadata {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let build_metadata = String::deserialize(deserializer)?;
+        BuildMetadata::from_str(&build_metadata).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for BuildMetadata {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
     }
 }
 
@@ -279,3 +473,100 @@ impl From<&str> for Label {
         Self(s.to_string())
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_parse {
+    use itertools::Itertools;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for version in [
+            "1.2.3",
+            "1.0.0-alpha",
+            "1.0.0-rc.1",
+            "1.0.0-rc.1.hotfix",
+            "1.0.0-alpha.beta",
+            "1.0.0-0.3.7",
+            "1.0.0-x-y-z.--",
+            "1.2.3+build.45",
+            "1.0.0-beta+exp.sha.5114f85",
+            "1.0.0+21AF26D3----117B344092BD",
+        ] {
+            assert_eq!(Version::from_str(version).unwrap().to_string(), version);
+        }
+    }
+
+    #[test]
+    fn components() {
+        let version = Version::from_str("1.0.0-rc.1.hotfix+build.45").unwrap();
+        assert_eq!(
+            version.stable_component(),
+            StableVersion {
+                major: 1,
+                minor: 0,
+                patch: 0
+            }
+        );
+        assert_eq!(
+            version.pre_component(),
+            Some(&Prerelease {
+                label: Label::from("rc"),
+                version: Some(1),
+                extra: vec![Identifier::AlphaNumeric("hotfix".to_string())],
+            })
+        );
+        assert_eq!(
+            version.build_metadata(),
+            Some(&BuildMetadata::from_str("build.45").unwrap())
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for version in [
+            "1.2",
+            "1.2.3.4",
+            "01.2.3",
+            "1.2.3-",
+            "1.2.3-rc..1",
+            "1.2.3-rc.01",
+            "1.2.3-rc_1",
+            "1.2.3+",
+            "1.2.3+build..1",
+            "+1.2.3",
+        ] {
+            assert!(Version::from_str(version).is_err(), "{version} should fail");
+        }
+    }
+
+    #[test]
+    fn precedence() {
+        // The example from https://semver.org/spec/v2.0.0.html#spec-item-11
+        let versions = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+        ]
+        .map(|version| Version::from_str(version).unwrap());
+        for (lower, higher) in versions.iter().tuple_windows() {
+            assert!(lower < higher, "{lower} should be less than {higher}");
+        }
+    }
+
+    #[test]
+    fn build_metadata_is_not_precedence() {
+        let with_build = Version::from_str("1.2.3+abc").unwrap();
+        let without_build = Version::from_str("1.2.3").unwrap();
+        assert_eq!(with_build.cmp_precedence(&without_build), Ordering::Equal);
+        assert_ne!(with_build, without_build);
+    }
+}
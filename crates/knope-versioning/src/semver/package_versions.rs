@@ -3,7 +3,8 @@ use std::{collections::BTreeMap, fmt::Debug, str::FromStr};
 use tracing::debug;
 
 use super::{
-    prerelease_map::PrereleaseMap, Label, PreVersion, Prerelease, Rule, StableVersion, Version,
+    prerelease_map::PrereleaseMap, BuildMetadata, Channels, Label, Prerelease, Rule, StableVersion,
+    Version,
 };
 use crate::semver::rule::Stable;
 
@@ -12,10 +13,14 @@ use crate::semver::rule::Stable;
 /// - The last version of each type of pre-release following the latest stable version
 ///
 /// So we might have 1.2.3, 1.2.4-rc.1, 1.3.0-beta.0, and 2.0.0-alpha.4
+///
+/// Build metadata is only kept for the latest version, since it doesn't affect bumping.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct PackageVersions {
     stable: StableVersion,
     prereleases: Prereleases,
+    build_metadata: Option<BuildMetadata>,
+    channels: Channels,
 }
 
 type Prereleases = BTreeMap<StableVersion, PrereleaseMap>;
@@ -28,31 +33,38 @@ impl PackageVersions {
     /// ## Parameters
     /// - `prefix`: Only tag names starting with this string will be considered.
     /// - `all_tags`: All tags in the repository.
-    pub fn from_tags<S: AsRef<str> + Debug>(prefix: Option<&str>, all_tags: &[S]) -> Self {
+    /// - `channels`: The order of pre-release labels, used to pick the latest pre-release.
+    pub fn from_tags<S: AsRef<str> + Debug>(
+        prefix: Option<&str>,
+        all_tags: &[S],
+        channels: Channels,
+    ) -> Self {
         let pattern = prefix
             .as_ref()
             .map_or_else(|| String::from("v"), |prefix| format!("{prefix}/v"));
         let mut tags = all_tags
             .iter()
-            .filter(|tag| tag.as_ref().starts_with(&pattern))
+            .filter_map(|tag| tag.as_ref().strip_prefix(&pattern))
             .peekable();
 
         if tags.peek().is_none() {
             debug!("No tags found matching pattern {pattern}");
         }
 
-        let mut current_versions = Self::default();
-        for tag in tags {
-            let version_string = tag.as_ref().replace(&pattern, "");
-            if let Ok(version) = Version::from_str(version_string.as_str()) {
-                match version {
-                    Version::Stable(stable) => {
-                        current_versions.stable = stable;
-                        break; // Only prereleases newer than the last stable version are relevant
-                    }
-                    Version::Pre(_) => {
-                        current_versions.update_version(version);
+        let mut current_versions = Self {
+            channels,
+            ..Self::default()
+        };
+        for version_string in tags {
+            if let Ok(version) = Version::from_str(version_string) {
+                if version.is_prerelease() {
+                    current_versions.update_version(version);
+                } else {
+                    current_versions.stable = version.stable_component();
+                    if current_versions.prereleases.is_empty() {
+                        current_versions.build_metadata = version.build_metadata().cloned();
                     }
+                    break; // Only prereleases newer than the last stable version are relevant
                 }
             }
         }
@@ -61,18 +73,23 @@ impl PackageVersions {
     }
 
     /// Consumes `self` to produce the most recent version (determined by order of tags).
+    ///
+    /// If there are multiple pre-releases of the newest stable version, the one in the most
+    /// stable channel is used.
     #[must_use]
     pub fn into_latest(mut self) -> Version {
-        self.prereleases.pop_last().map_or(
-            Version::Stable(self.stable),
-            |(stable_component, pres)| {
-                let pre_component = pres.into_last();
-                Version::Pre(PreVersion {
-                    stable_component,
-                    pre_component,
-                })
-            },
-        )
+        self.prereleases
+            .pop_last()
+            .map_or(Version::from(self.stable), |(stable_component, pres)| {
+                Version::pre(stable_component, pres.into_last(&self.channels))
+            })
+            .with_build_metadata(self.build_metadata)
+    }
+
+    /// The order of pre-release labels used for this package
+    #[must_use]
+    pub fn channels(&self) -> &Channels {
+        &self.channels
     }
 
     /// Replace or insert the version in the correct location if it's newer than the current
@@ -82,35 +99,44 @@ impl PackageVersions {
     /// If the version is a newer prerelease, it will overwrite the prerelease with
     /// the same stable component and label.
     pub fn update_version(&mut self, version: Version) {
-        match version {
-            Version::Stable(new) => {
-                if self.stable >= new {
-                    return;
-                }
-                self.stable = new;
-                self.prereleases.clear();
+        let Version {
+            stable_component,
+            pre_component,
+            build_metadata,
+        } = version;
+        let Some(pre_component) = pre_component else {
+            if self.stable >= stable_component {
+                return;
             }
-            Version::Pre(PreVersion {
-                stable_component,
-                pre_component,
-            }) => {
-                let recorded_pre = self
-                    .prereleases
-                    .get(&stable_component)
-                    .and_then(|pres| pres.get(&pre_component.label));
-                if let Some(recorded_pre) = recorded_pre {
-                    if recorded_pre >= &pre_component {
-                        return;
-                    }
-                }
-                if let Some(labels) = self.prereleases.get_mut(&stable_component) {
-                    labels.insert(pre_component);
-                } else {
-                    self.prereleases
-                        .insert(stable_component, PrereleaseMap::new(pre_component));
-                }
+            self.stable = stable_component;
+            self.prereleases.clear();
+            self.build_metadata = build_metadata;
+            return;
+        };
+        let recorded_pre = self
+            .prereleases
+            .get(&stable_component)
+            .and_then(|pres| pres.get(&pre_component.label));
+        if let Some(recorded_pre) = recorded_pre {
+            if recorded_pre >= &pre_component {
+                return;
             }
         }
+        if let Some(labels) = self.prereleases.get_mut(&stable_component) {
+            labels.insert(pre_component.clone());
+        } else {
+            self.prereleases
+                .insert(stable_component, PrereleaseMap::new(pre_component.clone()));
+        }
+        let is_latest = self
+            .prereleases
+            .last_key_value()
+            .is_some_and(|(stable, pres)| {
+                *stable == stable_component && *pres.last(&self.channels) == pre_component
+            });
+        if is_latest {
+            self.build_metadata = build_metadata;
+        }
     }
 
     /// Apply a Rule to a [`PackageVersion`], incrementing & resetting the correct components.
@@ -126,6 +152,7 @@ impl PackageVersions {
     ///
     /// Can fail if trying to run [`Rule::Release`] when there is no pre-release.
     pub fn bump(&mut self, rule: Rule) -> Result<(), PreReleaseNotFound> {
+        self.build_metadata = None;
         match rule {
             Rule::Major => self.update_version(bump_stable(self.stable, Stable::Major).into()),
             Rule::Minor => self.update_version(bump_stable(self.stable, Stable::Minor).into()),
@@ -163,7 +190,7 @@ impl PackageVersions {
             .and_then(|pres| {
                 pres.get(&label).map(|pre| {
                     debug!("Found existing pre-release version {pre}");
-                    pre.version + 1
+                    pre.version.map_or(0, |version| version + 1)
                 })
             })
             .unwrap_or_default();
@@ -174,10 +201,24 @@ impl PackageVersions {
 
         self.prereleases.clear();
 
-        self.update_version(Version::Pre(PreVersion {
-            stable_component,
-            pre_component: pre,
-        }));
+        self.update_version(Version::pre(stable_component, pre));
+    }
+
+    /// Make a snapshot of the next version: apply `stable_rule` to the stable version, then use
+    /// `prerelease` as-is for the pre-release component.
+    ///
+    /// Unlike [`Rule::Pre`], the pre-release isn't incremented from previous versions, since
+    /// snapshots are never tagged.
+    pub fn snapshot(&mut self, stable_rule: Stable, prerelease: Prerelease) {
+        self.build_metadata = None;
+        let stable_component = bump_stable(self.stable, stable_rule);
+        self.prereleases.clear();
+        self.update_version(Version::pre(stable_component, prerelease));
+    }
+
+    /// Attach build metadata to the latest version, replacing any that was there before.
+    pub fn set_build_metadata(&mut self, build_metadata: Option<BuildMetadata>) {
+        self.build_metadata = build_metadata;
     }
 }
 
@@ -237,6 +278,8 @@ impl From<StableVersion> for PackageVersions {
         Self {
             stable: version,
             prereleases: BTreeMap::new(),
+            build_metadata: None,
+            channels: Channels::default(),
         }
     }
 }
@@ -256,7 +299,7 @@ mod test_from_tags {
 
     use pretty_assertions::assert_eq;
 
-    use crate::semver::{PackageVersions, Prerelease, StableVersion, Version};
+    use crate::semver::{Channels, PackageVersions, Prerelease, StableVersion, Version};
     #[test]
     fn collect_all_newer_pre_releases() {
         let tags = [
@@ -269,7 +312,7 @@ mod test_from_tags {
         ]
         .map(String::from);
 
-        let versions = PackageVersions::from_tags(None, &tags);
+        let versions = PackageVersions::from_tags(None, &tags, Channels::default());
 
         assert_eq!(
             versions.stable(),
@@ -312,6 +355,54 @@ mod test_from_tags {
             Prerelease::new("beta".into(), 0)
         );
     }
+
+    #[test]
+    fn latest_pre_release_uses_channels() {
+        let tags = ["v1.3.0-rc.1", "v1.3.0-preview.0", "v1.2.3"].map(String::from);
+
+        let alphabetical = PackageVersions::from_tags(None, &tags, Channels::default());
+        assert_eq!(
+            alphabetical.into_latest(),
+            Version::from_str("1.3.0-rc.1").unwrap()
+        );
+
+        let channels = Channels::new(vec!["rc".into(), "preview".into()]);
+        let versions = PackageVersions::from_tags(None, &tags, channels);
+        assert_eq!(
+            versions.into_latest(),
+            Version::from_str("1.3.0-preview.0").unwrap()
+        );
+    }
+
+    #[test]
+    fn unmanaged_pre_releases_and_build_metadata() {
+        let tags = [
+            "v1.3.0-rc.1.hotfix+build.2",
+            "v1.3.0-rc.1",
+            "v1.3.0-alpha",
+            "v1.2.3+build.1",
+        ]
+        .map(String::from);
+
+        let versions = PackageVersions::from_tags(None, &tags, Channels::default());
+
+        assert_eq!(
+            versions.into_latest(),
+            Version::from_str("1.3.0-rc.1.hotfix+build.2").unwrap()
+        );
+    }
+
+    #[test]
+    fn stable_build_metadata() {
+        let tags = ["v1.2.3+build.1", "v1.2.2"].map(String::from);
+
+        let versions = PackageVersions::from_tags(None, &tags, Channels::default());
+
+        assert_eq!(
+            versions.into_latest(),
+            Version::from_str("1.2.3+build.1").unwrap()
+        );
+    }
 }
 
 #[cfg(test)]
@@ -494,6 +585,50 @@ mod test_bump {
         );
     }
 
+    #[test]
+    fn pre_after_unmanaged_pre() {
+        let mut versions = PackageVersions::from(Version::new(1, 2, 3, None));
+        versions.update_version(Version::from_str("1.3.0-rc.1.hotfix").unwrap());
+        versions
+            .bump(Rule::Pre {
+                label: Label::from("rc"),
+                stable_rule: Stable::Minor,
+            })
+            .unwrap();
+
+        assert_eq!(
+            versions.into_latest(),
+            Version::from_str("1.3.0-rc.2").unwrap()
+        );
+    }
+
+    #[test]
+    fn snapshot() {
+        let mut versions =
+            PackageVersions::from_tags(None, &["v1.3.0-rc.0", "v1.2.3"], Channels::default());
+        versions.snapshot(
+            Stable::Minor,
+            Prerelease::from_str("nightly.20261017.abc1234").unwrap(),
+        );
+        assert_eq!(
+            versions.into_latest(),
+            Version::from_str("1.3.0-nightly.20261017.abc1234").unwrap()
+        );
+    }
+
+    #[test]
+    fn bump_replaces_build_metadata() {
+        let mut versions = PackageVersions::from(Version::from_str("1.2.3+old").unwrap());
+        versions.bump(Rule::Patch).unwrap();
+        assert_eq!(versions.clone().into_latest(), Version::new(1, 2, 4, None));
+
+        versions.set_build_metadata(Some("abc1234".parse().unwrap()));
+        assert_eq!(
+            versions.into_latest(),
+            Version::from_str("1.2.4+abc1234").unwrap()
+        );
+    }
+
     #[test]
     fn release() {
         let mut versions = PackageVersions::default();
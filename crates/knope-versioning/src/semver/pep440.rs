@@ -0,0 +1,308 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+#[cfg(feature = "miette")]
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{BuildMetadata, Label, Prerelease, Version};
+
+/// Settings for converting between knope's [`Version`] and [PEP 440](https://peps.python.org/pep-0440/)
+/// versions, which Python packages must use.
+///
+/// Pre-release labels are mapped to PEP 440 pre-release kinds, so `1.2.0-rc.1` is written as
+/// `1.2.0rc1` and `1.2.0-dev.4` as `1.2.0.dev4`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Pep440 {
+    /// Overrides (or additions to) the default label mapping of `alpha` → `a`, `beta` → `b`,
+    /// `rc` → `rc`, and `dev` → `dev`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, PreKind>,
+}
+
+/// The kinds of pre-releases that PEP 440 supports.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreKind {
+    A,
+    B,
+    Rc,
+    Dev,
+}
+
+impl PreKind {
+    const DEFAULT_LABELS: [(&'static str, Self); 4] = [
+        ("alpha", Self::A),
+        ("beta", Self::B),
+        ("rc", Self::Rc),
+        ("dev", Self::Dev),
+    ];
+
+    fn from_spelling(s: &str) -> Option<Self> {
+        match s {
+            "a" | "alpha" => Some(Self::A),
+            "b" | "beta" => Some(Self::B),
+            "c" | "rc" | "pre" | "preview" => Some(Self::Rc),
+            "dev" => Some(Self::Dev),
+            _ => None,
+        }
+    }
+}
+
+impl Display for PreKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::A => write!(f, "a"),
+            Self::B => write!(f, "b"),
+            Self::Rc => write!(f, "rc"),
+            Self::Dev => write!(f, "dev"),
+        }
+    }
+}
+
+impl Pep440 {
+    fn kind(&self, label: &Label) -> Option<PreKind> {
+        self.labels.get(&label.0).copied().or_else(|| {
+            PreKind::DEFAULT_LABELS
+                .iter()
+                .find(|(default, _)| *default == label.0)
+                .map(|(_, kind)| *kind)
+        })
+    }
+
+    fn label(&self, kind: PreKind) -> Label {
+        self.labels
+            .iter()
+            .find(|(_, configured)| **configured == kind)
+            .map(|(label, _)| label.as_str())
+            .or_else(|| {
+                PreKind::DEFAULT_LABELS
+                    .iter()
+                    .find(|(default, default_kind)| {
+                        *default_kind == kind && !self.labels.contains_key(*default)
+                    })
+                    .map(|(default, _)| *default)
+            })
+            .map_or_else(|| Label(kind.to_string()), Label::from)
+    }
+
+    /// Render a [`Version`] in its normalized PEP 440 form.
+    ///
+    /// # Errors
+    ///
+    /// If the pre-release label has no PEP 440 equivalent, or the pre-release has identifiers
+    /// after its version (like `1.0.0-rc.1.hotfix`).
+    pub fn render(&self, version: &Version) -> Result<String, Error> {
+        let mut rendered = version.stable_component().to_string();
+        if let Some(pre) = version.pre_component() {
+            if !pre.extra.is_empty() {
+                return Err(Error::ExtraIdentifiers(version.to_string()));
+            }
+            let kind = self
+                .kind(&pre.label)
+                .ok_or_else(|| Error::UnknownLabel(pre.label.clone()))?;
+            let number = pre.version.unwrap_or(0);
+            if kind == PreKind::Dev {
+                rendered.push_str(&format!(".dev{number}"));
+            } else {
+                rendered.push_str(&format!("{kind}{number}"));
+            }
+        }
+        if let Some(build_metadata) = version.build_metadata() {
+            rendered.push('+');
+            rendered.push_str(&build_metadata.to_string().replace('-', "."));
+        }
+        Ok(rendered)
+    }
+
+    /// Parse a PEP 440 version (in any of its allowed spellings) into a [`Version`].
+    ///
+    /// Only versions with exactly three release segments and at most one pre-release or
+    /// development segment are supported.
+    ///
+    /// # Errors
+    ///
+    /// If the version isn't valid PEP 440, or uses features that don't map to Semantic Versioning
+    /// (like epochs or post-releases).
+    pub fn parse(&self, s: &str) -> Result<Version, Error> {
+        let invalid = || Error::Invalid(s.to_string());
+        let lowercase = s.trim().to_lowercase();
+        let version = lowercase.strip_prefix('v').unwrap_or(&lowercase);
+        let (version, local) = version
+            .split_once('+')
+            .map_or((version, None), |(version, local)| (version, Some(local)));
+
+        let mut release = Vec::with_capacity(3);
+        let mut rest = version;
+        loop {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let (segment, after) = rest.split_at(end);
+            release.push(segment.parse::<u64>().map_err(|_| invalid())?);
+            rest = after;
+            match rest.strip_prefix('.') {
+                Some(after_dot) if after_dot.starts_with(|c: char| c.is_ascii_digit()) => {
+                    rest = after_dot;
+                }
+                _ => break,
+            }
+        }
+        let [major, minor, patch]: [u64; 3] = release.try_into().map_err(|_| invalid())?;
+
+        let pre = if rest.is_empty() {
+            None
+        } else {
+            let rest = rest.strip_prefix(['-', '_', '.']).unwrap_or(rest);
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            let (spelling, rest) = rest.split_at(end);
+            let kind = PreKind::from_spelling(spelling).ok_or_else(invalid)?;
+            let rest = rest.strip_prefix(['-', '_', '.']).unwrap_or(rest);
+            let number = if rest.is_empty() {
+                0
+            } else {
+                rest.parse::<u64>().map_err(|_| invalid())?
+            };
+            Some(Prerelease {
+                label: self.label(kind),
+                version: Some(number),
+                extra: Vec::new(),
+            })
+        };
+
+        let build_metadata = local
+            .map(|local| BuildMetadata::from_str(&local.replace(['-', '_'], ".")))
+            .transpose()
+            .map_err(|_| invalid())?;
+
+        Ok(Version::new(major, minor, patch, pre).with_build_metadata(build_metadata))
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+pub enum Error {
+    #[error("Found unsupported PEP 440 version {0}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::pep440::invalid),
+            help(
+                "Knope supports PEP 440 versions with three release segments and at most one \
+                pre-release or development segment, like 1.2.0rc1 or 1.2.0.dev4"
+            ),
+            url("https://knope.tech/reference/config-file/packages/#pep440")
+        )
+    )]
+    Invalid(String),
+    #[error("The pre-release label {0} has no PEP 440 equivalent")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::pep440::unknown_label),
+            help("Map the label to a, b, rc, or dev in the package's `pep440.labels` table"),
+            url("https://knope.tech/reference/config-file/packages/#pep440")
+        )
+    )]
+    UnknownLabel(Label),
+    #[error("The version {0} has too many pre-release identifiers to be written as PEP 440")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::pep440::extra_identifiers),
+            help("PEP 440 pre-releases can only have a label and a number, like rc.1"),
+            url("https://knope.tech/reference/config-file/packages/#pep440")
+        )
+    )]
+    ExtraIdentifiers(String),
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn render() {
+        let pep440 = Pep440::default();
+        for (semver, expected) in [
+            ("1.2.0", "1.2.0"),
+            ("1.2.0-alpha.3", "1.2.0a3"),
+            ("1.2.0-beta.0", "1.2.0b0"),
+            ("1.2.0-rc.1", "1.2.0rc1"),
+            ("1.2.0-dev.4", "1.2.0.dev4"),
+            ("1.2.0-rc", "1.2.0rc0"),
+            ("1.2.0-rc.1+build-5", "1.2.0rc1+build.5"),
+        ] {
+            let version = Version::from_str(semver).unwrap();
+            assert_eq!(pep440.render(&version).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn render_errors() {
+        let pep440 = Pep440::default();
+        assert!(matches!(
+            pep440.render(&Version::from_str("1.2.0-preview.1").unwrap()),
+            Err(Error::UnknownLabel(_))
+        ));
+        assert!(matches!(
+            pep440.render(&Version::from_str("1.2.0-rc.1.hotfix").unwrap()),
+            Err(Error::ExtraIdentifiers(_))
+        ));
+    }
+
+    #[test]
+    fn parse() {
+        let pep440 = Pep440::default();
+        for (pep440_version, expected) in [
+            ("1.2.0", "1.2.0"),
+            ("1.2.0a3", "1.2.0-alpha.3"),
+            ("1.2.0-ALPHA.3", "1.2.0-alpha.3"),
+            ("1.2.0b", "1.2.0-beta.0"),
+            ("1.2.0c1", "1.2.0-rc.1"),
+            ("1.2.0-rc.1", "1.2.0-rc.1"),
+            ("1.2.0.dev4", "1.2.0-dev.4"),
+            ("v1.2.0rc1+local.5", "1.2.0-rc.1+local.5"),
+        ] {
+            assert_eq!(
+                pep440.parse(pep440_version).unwrap(),
+                Version::from_str(expected).unwrap(),
+                "{pep440_version}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let pep440 = Pep440::default();
+        for invalid in ["1.2", "1!1.2.0", "1.2.0.post1", "1.2.0rc1.dev2", "1.2.0rcx"] {
+            assert!(pep440.parse(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn custom_labels() {
+        let pep440 = Pep440 {
+            labels: BTreeMap::from([
+                ("preview".to_string(), PreKind::Rc),
+                ("nightly".to_string(), PreKind::Dev),
+            ]),
+        };
+        let version = Version::from_str("1.2.0-preview.2").unwrap();
+        assert_eq!(pep440.render(&version).unwrap(), "1.2.0rc2");
+        assert_eq!(pep440.parse("1.2.0rc2").unwrap(), version);
+        assert_eq!(
+            pep440.parse("1.2.0.dev1").unwrap(),
+            Version::from_str("1.2.0-nightly.1").unwrap()
+        );
+        assert_eq!(
+            pep440.parse("1.2.0a1").unwrap(),
+            Version::from_str("1.2.0-alpha.1").unwrap()
+        );
+    }
+}
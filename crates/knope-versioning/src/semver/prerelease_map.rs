@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use super::{Label, Prerelease};
+use super::{Channels, Label, Prerelease};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 /// Used to track the various pre-releases of a version, can never be empty
@@ -14,11 +14,17 @@ impl PrereleaseMap {
         Self(map)
     }
 
+    /// The pre-release in the most stable channel
+    pub(crate) fn into_last(self, channels: &Channels) -> Prerelease {
+        self.last(channels).clone()
+    }
+
+    /// The pre-release in the most stable channel
     #[allow(clippy::unwrap_used)] // Map is not allowed to be empty ever
-    pub(crate) fn into_last(mut self) -> Prerelease {
+    pub(crate) fn last(&self, channels: &Channels) -> &Prerelease {
         self.0
-            .pop_last()
-            .map(|(_label, prerelease)| prerelease)
+            .values()
+            .max_by(|pre, other| channels.cmp_prereleases(pre, other))
             .unwrap()
     }
 
@@ -1,10 +1,12 @@
 use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
-use tracing::debug;
 
 use super::Label;
-use crate::changes::{Change, ChangeType};
+use crate::{
+    changes::{Change, ChangeType},
+    release_notes::Sections,
+};
 
 /// The various rules that can be used when bumping semantic versions.
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -40,20 +42,11 @@ pub enum Stable {
     Patch,
 }
 
+/// The rule for a set of changes using the default bump levels, where every custom change type is a
+/// patch. Use [`Sections::stable_rule`] to respect the bump levels configured for custom types.
 impl<'a, T: IntoIterator<Item = &'a Change>> From<T> for Stable {
     fn from(changes: T) -> Self {
-        changes
-            .into_iter()
-            .map(|change| {
-                let rule = Self::from(&change.change_type);
-                debug!(
-                    "{change_source}\n\timplies rule {rule}",
-                    change_source = change.original_source
-                );
-                rule
-            })
-            .max()
-            .unwrap_or_default()
+        Sections::default().stable_rule(changes).unwrap_or_default()
     }
 }
 
@@ -85,12 +78,12 @@ impl PartialOrd for Stable {
     }
 }
 
+/// The rule for a single type of change using the default bump levels.
 impl From<&ChangeType> for Stable {
     fn from(value: &ChangeType) -> Self {
-        match value {
-            ChangeType::Feature => Self::Minor,
-            ChangeType::Breaking => Self::Major,
-            ChangeType::Custom(_) | ChangeType::Fix => Self::Patch,
-        }
+        Sections::default()
+            .bump_level(value)
+            .rule()
+            .unwrap_or_default()
     }
 }
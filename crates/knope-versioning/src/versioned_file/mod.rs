@@ -10,7 +10,7 @@ use serde::{Serialize, Serializer};
 
 use crate::{
     action::ActionSet::{Single, Two},
-    semver::Version,
+    semver::{pep440, Pep440, Version},
     versioned_file::cargo_lock::CargoLock,
     Action,
 };
@@ -51,7 +51,7 @@ impl VersionedFile {
             Format::CargoLock => CargoLock::new(config.as_path(), &content)
                 .map(VersionedFile::CargoLock)
                 .map_err(Error::CargoLock),
-            Format::PyProject => PyProject::new(config.as_path(), content)
+            Format::PyProject => PyProject::new(config.as_path(), content, config.pep440.clone())
                 .map(VersionedFile::PyProject)
                 .map_err(Error::PyProject),
             Format::PubSpec => PubSpec::new(config.as_path(), content)
@@ -111,7 +111,10 @@ impl VersionedFile {
                 .set_version(new_version, dependency)
                 .map(Self::CargoLock)
                 .map_err(SetError::CargoLock),
-            Self::PyProject(pyproject) => Ok(Self::PyProject(pyproject.set_version(new_version))),
+            Self::PyProject(pyproject) => pyproject
+                .set_version(new_version)
+                .map_err(SetError::Pep440)
+                .map(Self::PyProject),
             Self::PubSpec(pubspec) => pubspec
                 .set_version(new_version)
                 .map_err(SetError::Yaml)
@@ -168,6 +171,9 @@ pub enum SetError {
     #[error(transparent)]
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
     CargoLock(#[from] cargo_lock::SetError),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Pep440(#[from] pep440::Error),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -262,6 +268,8 @@ pub struct Config {
     pub(crate) format: Format,
     /// If, within the file, we're versioning a dependency (not the entire package)
     pub dependency: Option<String>,
+    /// How to read and write PEP 440 versions, for Python files
+    pub(crate) pep440: Option<Pep440>,
 }
 
 impl Config {
@@ -280,9 +288,18 @@ impl Config {
             parent,
             format,
             dependency,
+            pep440: None,
         })
     }
 
+    /// Read and write the version of Python files in PEP 440 form instead of Semantic Versioning.
+    /// Has no effect on other file types.
+    #[must_use]
+    pub fn with_pep440(mut self, pep440: Option<Pep440>) -> Self {
+        self.pep440 = pep440;
+        self
+    }
+
     #[must_use]
     pub fn as_path(&self) -> RelativePathBuf {
         self.parent.as_ref().map_or_else(
@@ -303,26 +320,31 @@ impl Config {
                 format: Format::Cargo,
                 parent: None,
                 dependency: None,
+                pep440: None,
             },
             Config {
                 parent: None,
                 format: Format::GoMod,
                 dependency: None,
+                pep440: None,
             },
             Config {
                 parent: None,
                 format: Format::PackageJson,
                 dependency: None,
+                pep440: None,
             },
             Config {
                 parent: None,
                 format: Format::PubSpec,
                 dependency: None,
+                pep440: None,
             },
             Config {
                 parent: None,
                 format: Format::PyProject,
                 dependency: None,
+                pep440: None,
             },
         ]
     }
@@ -7,7 +7,10 @@ use serde::Deserialize;
 use thiserror::Error;
 use toml::Spanned;
 
-use crate::{action::Action, semver::Version};
+use crate::{
+    action::Action,
+    semver::{pep440, Pep440, Version},
+};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PyProject {
@@ -15,28 +18,44 @@ pub struct PyProject {
     raw_toml: String,
     parsed: Toml,
     pub(super) version: Version,
+    pep440: Option<Pep440>,
     diff: Option<String>,
 }
 
 impl PyProject {
-    pub(crate) fn new(path: RelativePathBuf, raw_toml: String) -> Result<Self, Error> {
+    /// Parse a `pyproject.toml` file.
+    ///
+    /// If `pep440` is set, versions are read and written in PEP 440 form instead of as
+    /// Semantic Versions.
+    pub(crate) fn new(
+        path: RelativePathBuf,
+        raw_toml: String,
+        pep440: Option<Pep440>,
+    ) -> Result<Self, Error> {
         match toml::from_str::<Toml>(&raw_toml) {
             Ok(parsed) => parsed
                 .version(&path)
-                .and_then(|version| Version::from_str(version).map_err(Error::from))
+                .and_then(|version| match &pep440 {
+                    Some(pep440) => pep440.parse(version).map_err(Error::from),
+                    None => Version::from_str(version).map_err(Error::from),
+                })
                 .map(|version| PyProject {
                     path,
                     raw_toml,
                     parsed,
                     version,
+                    pep440,
                     diff: None,
                 }),
             Err(err) => Err(Error::Deserialization(path, err)),
         }
     }
 
-    pub(crate) fn set_version(mut self, new_version: &Version) -> Self {
-        let version_str = new_version.to_string();
+    pub(crate) fn set_version(mut self, new_version: &Version) -> Result<Self, pep440::Error> {
+        let version_str = match &self.pep440 {
+            Some(pep440) => pep440.render(new_version)?,
+            None => new_version.to_string(),
+        };
         let (poetry_version, project_version) = self.parsed.versions();
 
         for version in [poetry_version, project_version].into_iter().flatten() {
@@ -46,7 +65,7 @@ impl PyProject {
             self.raw_toml.replace_range(start..end, &version_str);
         }
         self.diff = Some(version_str);
-        self
+        Ok(self)
     }
 
     pub(crate) fn write(self) -> Option<Action> {
@@ -99,6 +118,9 @@ pub enum Error {
     #[error(transparent)]
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
     Semver(#[from] crate::semver::Error),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Pep440(#[from] pep440::Error),
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
@@ -155,6 +177,7 @@ struct Metadata {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use pretty_assertions::assert_eq;
 
@@ -169,7 +192,7 @@ mod tests {
         "#;
 
         assert_eq!(
-            PyProject::new(RelativePathBuf::new(), content.to_string())
+            PyProject::new(RelativePathBuf::new(), content.to_string(), None)
                 .unwrap()
                 .version,
             Version::from_str("0.1.0-rc.0").unwrap()
@@ -185,7 +208,7 @@ mod tests {
         "#;
 
         assert_eq!(
-            PyProject::new(RelativePathBuf::new(), content.to_string())
+            PyProject::new(RelativePathBuf::new(), content.to_string(), None)
                 .unwrap()
                 .version,
             Version::from_str("0.1.0-rc.0").unwrap()
@@ -205,7 +228,7 @@ mod tests {
         "#;
 
         assert_eq!(
-            PyProject::new(RelativePathBuf::new(), content.to_string())
+            PyProject::new(RelativePathBuf::new(), content.to_string(), None)
                 .unwrap()
                 .version,
             Version::from_str("0.1.0-rc.0").unwrap()
@@ -224,7 +247,7 @@ mod tests {
         version = "2.3.4"
         "#;
 
-        match PyProject::new(RelativePathBuf::new(), content.to_string()) {
+        match PyProject::new(RelativePathBuf::new(), content.to_string(), None) {
             Err(Error::InconsistentVersions {
                 poetry, project, ..
             }) => {
@@ -247,10 +270,15 @@ mod tests {
         version = "0.1.0-rc.0"
         "#;
 
-        let pyproject =
-            PyProject::new(RelativePathBuf::from("beep/boop"), String::from(content)).unwrap();
+        let pyproject = PyProject::new(
+            RelativePathBuf::from("beep/boop"),
+            String::from(content),
+            None,
+        )
+        .unwrap();
         let action = pyproject
             .set_version(&Version::from_str("1.2.3-rc.4").unwrap())
+            .unwrap()
             .write()
             .expect("Diff to write");
 
@@ -270,4 +298,41 @@ mod tests {
         };
         assert_eq!(action, expected);
     }
+
+    #[test]
+    fn test_pep440() {
+        let content = r#"
+        [project]
+        name = "tester"
+        version = "1.2.0a3"
+        "#;
+
+        let pyproject = PyProject::new(
+            RelativePathBuf::from("pyproject.toml"),
+            String::from(content),
+            Some(Pep440::default()),
+        )
+        .unwrap();
+        assert_eq!(
+            pyproject.version,
+            Version::from_str("1.2.0-alpha.3").unwrap()
+        );
+        let action = pyproject
+            .set_version(&Version::from_str("1.2.0-rc.1").unwrap())
+            .unwrap()
+            .write()
+            .expect("Diff to write");
+
+        let expected = Action::WriteToFile {
+            content: r#"
+        [project]
+        name = "tester"
+        version = "1.2.0rc1"
+        "#
+            .to_string(),
+            path: RelativePathBuf::from("pyproject.toml"),
+            diff: "1.2.0rc1".to_string(),
+        };
+        assert_eq!(action, expected);
+    }
 }
@@ -3,17 +3,18 @@ use std::path::Path;
 use ::toml::{from_str, to_string, Spanned};
 use indexmap::IndexMap;
 use itertools::Itertools;
-use knope_versioning::package::Name;
+use knope_versioning::{package::Name, semver::BuildMetadata};
 use miette::{Diagnostic, IntoDiagnostic, Result, SourceSpan};
 pub(crate) use package::Package;
+use relative_path::RelativePathBuf;
 use serde::Serialize;
 use thiserror::Error;
 use toml::ConfigLoader;
 
 use crate::{
     fs,
-    integrations::git,
-    step::{PrepareRelease, Step},
+    integrations::{git, git::Since},
+    step::{CreateChangeFile, PrepareRelease, Step},
     variables::Variable,
     workflow::Workflow,
 };
@@ -37,6 +38,8 @@ pub(crate) struct Config {
     pub(crate) github: Option<GitHub>,
     /// Optional configuration to communicate with a Gitea instance
     pub(crate) gitea: Option<Gitea>,
+    /// Where change files are, if not `.changeset`
+    pub(crate) changeset_directory: Option<RelativePathBuf>,
 }
 
 impl Config {
@@ -64,10 +67,33 @@ impl Config {
         }
     }
 
+    /// Set where to start looking for commits for all `PrepareRelease` steps in all workflows.
+    pub(crate) fn set_since(&mut self, since: &Since) {
+        for workflow in &mut self.workflows {
+            workflow.set_since(since);
+        }
+    }
+
+    /// Override the inputs of all `CreateChangeFile` steps in all workflows.
+    pub(crate) fn set_change_file_inputs(&mut self, inputs: &CreateChangeFile) {
+        for workflow in &mut self.workflows {
+            workflow.set_change_file_inputs(inputs);
+        }
+    }
+
+    /// Set the build metadata for all `PrepareRelease` and `BumpVersion` steps in all workflows.
+    pub(crate) fn set_build_metadata(&mut self, build_metadata: &BuildMetadata) {
+        for workflow in &mut self.workflows {
+            workflow.set_build_metadata(build_metadata);
+        }
+    }
+
     /// Write out the Config to `knope.toml`.
     pub(crate) fn write_out(mut self) -> Result<()> {
         #[derive(Serialize)]
         struct SimpleConfig {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            changeset_directory: Option<RelativePathBuf>,
             #[serde(skip_serializing_if = "Option::is_none")]
             package: Option<knope_config::Package>,
             #[serde(skip_serializing_if = "IndexMap::is_empty")]
@@ -93,6 +119,7 @@ impl Config {
         };
 
         let config = SimpleConfig {
+            changeset_directory: self.changeset_directory,
             package,
             packages,
             workflows: self.workflows,
@@ -163,6 +190,7 @@ impl TryFrom<(ConfigLoader, String)> for Config {
             jira: config.jira.map(Spanned::into_inner),
             github: config.github.map(Spanned::into_inner),
             gitea: config.gitea.map(Spanned::into_inner),
+            changeset_directory: config.changeset_directory,
         })
     }
 }
@@ -291,6 +319,7 @@ pub(crate) fn generate() -> Result<Config, package::Error> {
         github,
         gitea,
         packages,
+        changeset_directory: None,
     })
 }
 
@@ -348,7 +377,7 @@ fn generate_workflows(has_forge: bool, packages: &[Package]) -> Vec<Workflow> {
         Workflow {
             name: String::from("document-change"),
             help_text: None,
-            steps: vec![Step::CreateChangeFile],
+            steps: vec![Step::CreateChangeFile(CreateChangeFile::default())],
         },
     ];
 
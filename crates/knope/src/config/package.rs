@@ -1,9 +1,16 @@
 use std::{ops::Range, path::PathBuf, str::FromStr};
 
 use ::toml::Spanned;
+use glob::Pattern;
 use itertools::Itertools;
-use knope_config::{Assets, ChangelogSection};
-use knope_versioning::{package, versioned_file::cargo, UnknownFile, VersionedFileConfig};
+use knope_config::{Assets, ChangelogSection, ScopesAndPaths};
+use knope_versioning::{
+    package,
+    release_notes::CommitLinks,
+    semver::{Channels, Pep440},
+    versioned_file::cargo,
+    UnknownFile, VersionedFileConfig,
+};
 use miette::Diagnostic;
 use relative_path::{RelativePath, RelativePathBuf};
 use thiserror::Error;
@@ -21,11 +28,29 @@ pub struct Package {
     pub(crate) changelog: Option<RelativePathBuf>,
     /// Optional scopes that can be used to filter commits when running [`Step::PrepareRelease`].
     pub(crate) scopes: Option<Vec<String>>,
+    /// Optional globs of files that commits must touch to be considered for this package.
+    pub(crate) paths: Option<Vec<Pattern>>,
+    pub(crate) scopes_and_paths: ScopesAndPaths,
+    /// Regular expressions matching commit messages to ignore.
+    pub(crate) ignore_commits: Vec<String>,
     /// Extra sections that should be added to the changelog from custom footers in commit messages
     /// or change set types.
     pub(crate) extra_changelog_sections: Vec<ChangelogSection>,
+    /// Which commit details to link in release notes.
+    pub(crate) commit_links: Option<CommitLinks>,
+    /// Whether to add a "Contributors" section to release notes.
+    pub(crate) contributors: bool,
+    /// Renders new releases in the changelog instead of the default layout.
+    pub(crate) changelog_template: Option<RelativePathBuf>,
+    /// Renders the body of forge releases, if not the changelog template.
+    pub(crate) release_template: Option<RelativePathBuf>,
     pub(crate) assets: Option<Assets>,
     pub(crate) ignore_go_major_versioning: bool,
+    pub(crate) pep440: Option<Pep440>,
+    /// The order of pre-release labels, from least to most stable.
+    pub(crate) prerelease_channels: Channels,
+    /// Where change files for this package are, if not the project-wide directory.
+    pub(crate) changeset_directory: Option<RelativePathBuf>,
 }
 
 impl Package {
@@ -139,10 +164,20 @@ impl Package {
                     name: package::Name::Custom(member.name.clone()),
                     versioned_files,
                     scopes: Some(vec![member.name.clone()]),
+                    paths: None,
+                    scopes_and_paths: ScopesAndPaths::default(),
+                    ignore_commits: Vec::new(),
                     changelog: None,
                     extra_changelog_sections: vec![],
+                    commit_links: None,
+                    contributors: false,
+                    changelog_template: None,
+                    release_template: None,
                     assets: None,
                     ignore_go_major_versioning: false,
+                    pep440: None,
+                    prerelease_channels: Channels::default(),
+                    changeset_directory: None,
                 }
             })
             .collect())
@@ -157,15 +192,37 @@ impl Package {
             versioned_files,
             changelog,
             scopes,
+            paths,
+            scopes_and_paths,
+            ignore_commits,
             extra_changelog_sections,
+            commit_links,
+            contributors,
+            changelog_template,
+            release_template,
             assets,
             ignore_go_major_versioning,
+            pep440,
+            prerelease_channels,
+            changeset_directory,
         } = package;
+        let paths = paths
+            .map(|paths| {
+                paths
+                    .into_iter()
+                    .map(|path| {
+                        Pattern::new(&path)
+                            .map_err(|source| VersionedFileError::InvalidPath { path, source })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
         let versioned_files = versioned_files
             .into_iter()
             .map(|spanned| {
                 let span = spanned.span();
                 VersionedFileConfig::try_from(spanned.into_inner())
+                    .map(|config| config.with_pep440(pep440.clone()))
                     .map_err(|source| VersionedFileError::UnknownFile {
                         source,
                         span: span.clone(),
@@ -190,9 +247,19 @@ impl Package {
             versioned_files,
             changelog,
             scopes,
+            paths,
+            scopes_and_paths,
+            ignore_commits,
             extra_changelog_sections,
+            commit_links,
+            contributors,
+            changelog_template,
+            release_template,
             assets,
             ignore_go_major_versioning,
+            pep440,
+            prerelease_channels,
+            changeset_directory,
         })
     }
 }
@@ -207,9 +274,24 @@ impl From<Package> for knope_config::Package {
                 .collect(),
             changelog: package.changelog,
             scopes: package.scopes,
+            paths: package.paths.map(|paths| {
+                paths
+                    .into_iter()
+                    .map(|pattern| pattern.as_str().to_string())
+                    .collect()
+            }),
+            scopes_and_paths: package.scopes_and_paths,
+            ignore_commits: package.ignore_commits,
             extra_changelog_sections: package.extra_changelog_sections,
+            commit_links: package.commit_links,
+            contributors: package.contributors,
+            changelog_template: package.changelog_template,
+            release_template: package.release_template,
             assets: package.assets,
             ignore_go_major_versioning: package.ignore_go_major_versioning,
+            pep440: package.pep440,
+            prerelease_channels: package.prerelease_channels,
+            changeset_directory: package.changeset_directory,
         }
     }
 }
@@ -245,6 +327,16 @@ pub enum VersionedFileError {
         #[label("Declared here")]
         span: Range<usize>,
     },
+    #[error("Invalid glob in paths: {path}")]
+    #[diagnostic(
+        code(config::invalid_path_glob),
+        url("https://knope.tech/reference/config-file/packages/#paths")
+    )]
+    InvalidPath {
+        path: String,
+        #[source]
+        source: glob::PatternError,
+    },
 }
 
 #[derive(Debug, Diagnostic, thiserror::Error)]
@@ -1,6 +1,8 @@
 use indexmap::IndexMap;
 use itertools::Itertools;
 use knope_config::Package;
+use knope_versioning::{release_notes, release_notes::Forge};
+use relative_path::RelativePathBuf;
 use serde::{Deserialize, Serialize};
 use toml::Spanned;
 
@@ -20,6 +22,8 @@ pub(crate) struct ConfigLoader {
     pub(crate) github: Option<Spanned<GitHub>>,
     /// Optional configuration to talk to a Gitea instance
     pub(crate) gitea: Option<Spanned<Gitea>>,
+    /// Where change files are, if not `.changeset`
+    pub(crate) changeset_directory: Option<RelativePathBuf>,
 }
 
 #[cfg(test)]
@@ -70,6 +74,16 @@ pub(crate) struct Jira {
     pub(crate) project: String,
 }
 
+impl Jira {
+    /// The Jira project that changes can refer to issues in, for links in release notes.
+    pub(crate) fn issue_tracker(&self) -> release_notes::Jira {
+        release_notes::Jira {
+            url: self.url.clone(),
+            project: self.project.clone(),
+        }
+    }
+}
+
 /// Details needed to use steps that interact with GitHub.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub(crate) struct GitHub {
@@ -79,6 +93,16 @@ pub(crate) struct GitHub {
     pub(crate) repo: String,
 }
 
+impl GitHub {
+    /// Where the repository lives, for links in release notes.
+    pub(crate) fn forge(&self) -> Forge {
+        Forge::GitHub {
+            owner: self.owner.clone(),
+            repo: self.repo.clone(),
+        }
+    }
+}
+
 /// Details needed to use steps that interact with a Gitea instance.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
@@ -95,6 +119,15 @@ impl Gitea {
     /// This lists all known gitea hosts, so we can easily generate the gitea config
     pub(crate) const KNOWN_PUBLIC_GITEA_HOSTS: &'static [&'static str] = &["codeberg.org"];
 
+    /// Where the repository lives, for links in release notes.
+    pub(crate) fn forge(&self) -> Forge {
+        Forge::Gitea {
+            host: self.host.clone(),
+            owner: self.owner.clone(),
+            repo: self.repo.clone(),
+        }
+    }
+
     fn get_base_url(&self) -> String {
         format!("{host}/api/v1", host = self.host)
     }
@@ -1,14 +1,18 @@
 use std::{
     collections::{HashMap, HashSet, VecDeque},
     env::current_dir,
+    path::{Path, PathBuf},
     str::FromStr,
 };
 
-use git2::{build::CheckoutBuilder, Branch, BranchType, IndexAddOption, Repository};
+use git2::{build::CheckoutBuilder, Branch, BranchType, Delta, IndexAddOption, Repository, Sort};
 use gix::{object::Kind, refs::transaction::PreviousValue, ObjectId};
+use glob::Pattern;
 use itertools::Itertools;
+use knope_versioning::changes::conventional_commit::Author;
 use miette::Diagnostic;
 use relative_path::RelativePathBuf;
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
 use crate::{fs, prompt, prompt::select, state, state::State, step::issues::Issue, RunType};
@@ -127,6 +131,17 @@ enum ErrorKind {
     Tags(#[from] gix::reference::iter::init::Error),
     #[error("Could not find head commit: {0}")]
     HeadCommit(#[from] gix::reference::head_commit::Error),
+    #[error("Could not find the Git revision {revision}")]
+    #[diagnostic(
+        code(git::unknown_revision),
+        help("The `since` option must be a tag, branch, or commit in this repository, or `merge-base:` followed by one."),
+        url("https://knope.tech/reference/config-file/steps/prepare-release/#since")
+    )]
+    UnknownRevision {
+        revision: String,
+        #[source]
+        source: git2::Error,
+    },
     #[error("Could not determine Git committer to commit changes")]
     #[diagnostic(
         code(git::no_committer),
@@ -150,6 +165,10 @@ enum ErrorKind {
     PeelOid(#[from] gix::reference::peel::Error),
     #[error("Could not walk commits back from HEAD: {0}")]
     RevisionWalk(#[from] gix::revision::walk::Error),
+    #[error("Could not find commit: {0}")]
+    FindCommit(#[from] gix::object::find::existing::with_conversion::Error),
+    #[error("The date of the tag {0} is out of range")]
+    InvalidTagDate(String),
 }
 
 /// Rebase the current branch onto the selected one.
@@ -379,25 +398,218 @@ pub(crate) fn add_files(file_names: &[RelativePathBuf]) -> Result<(), Error> {
     index.write().map_err(Error::from)
 }
 
-/// Find every commit that appears only _after_ a specific tag.
+/// Find every commit that appears only _after_ a specific tag (or `range.since`).
 ///
 /// This builds a complete set of every commit in the repository, because branching and merging
 /// means that there could be paths which jump _behind_ the target tag... and we want to exclude
 /// those as well. There's probably a way to optimize performance with some cool graph magic
 /// eventually, but this is good enough for now.
-pub(crate) fn get_commit_messages_after_tag(tag: &str) -> Result<Vec<String>, Error> {
-    let repo = gix::open(".")?;
+pub(crate) fn get_commit_messages_after_tag(
+    tag: &str,
+    range: &CommitRange,
+) -> Result<Vec<String>, Error> {
+    Ok(get_commits_after_tag(tag, range, &[])?
+        .into_iter()
+        .map(|commit| commit.message)
+        .collect())
+}
+
+/// Where to start looking for commits, instead of the last version tag.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(from = "String", into = "String")]
+pub(crate) enum Since {
+    /// A tag, branch, or commit. Only commits which aren't reachable from it are used.
+    Revision(String),
+    /// The merge base of `HEAD` and a revision (like `main`), written as `merge-base:main`.
+    MergeBase(String),
+}
+
+impl From<String> for Since {
+    fn from(value: String) -> Self {
+        match value.strip_prefix("merge-base:") {
+            Some(revision) => Self::MergeBase(revision.trim().to_string()),
+            None => Self::Revision(value),
+        }
+    }
+}
+
+impl FromStr for Since {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s.to_string()))
+    }
+}
+
+impl From<Since> for String {
+    fn from(since: Since) -> Self {
+        match since {
+            Since::Revision(revision) => revision,
+            Since::MergeBase(revision) => format!("merge-base:{revision}"),
+        }
+    }
+}
+
+impl Since {
+    /// Find the commit this points to.
+    fn resolve(&self) -> Result<ObjectId, Error> {
+        let git2_repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+        let id = match self {
+            Self::Revision(revision) => find_commit(&git2_repo, revision)?,
+            Self::MergeBase(revision) => {
+                let other = find_commit(&git2_repo, revision)?;
+                let head = find_commit(&git2_repo, "HEAD")?;
+                git2_repo.merge_base(head, other)?
+            }
+        };
+        debug!("Using commits since {id} ({self:?})");
+        to_object_id(id)
+    }
+}
+
+/// Find the commit that a tag, branch, or other revision points to.
+fn find_commit(repo: &Repository, revision: &str) -> Result<git2::Oid, Error> {
+    repo.revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .map(|commit| commit.id())
+        .map_err(|source| {
+            ErrorKind::UnknownRevision {
+                revision: revision.to_string(),
+                source,
+            }
+            .into()
+        })
+}
+
+fn to_object_id(id: git2::Oid) -> Result<ObjectId, Error> {
+    ObjectId::try_from(id.as_bytes()).map_err(|_| {
+        ErrorKind::UnknownRevision {
+            revision: id.to_string(),
+            source: git2::Error::from_str("Unsupported object ID"),
+        }
+        .into()
+    })
+}
+
+/// The date that a tag was created, or the date of its commit if it's a lightweight tag.
+pub(crate) fn tag_date(tag: &str) -> Result<time::Date, Error> {
+    let repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+    let object = repo
+        .revparse_single(&format!("refs/tags/{tag}"))
+        .map_err(|source| ErrorKind::UnknownRevision {
+            revision: tag.to_string(),
+            source,
+        })?;
+    let time = match object.as_tag().and_then(git2::Tag::tagger) {
+        Some(tagger) => tagger.when(),
+        None => object.peel_to_commit()?.time(),
+    };
+    let seconds = time.seconds() + i64::from(time.offset_minutes()) * 60;
+    Ok(time::OffsetDateTime::from_unix_timestamp(seconds)
+        .map_err(|_| ErrorKind::InvalidTagDate(tag.to_string()))?
+        .date())
+}
+
+/// Which commits to consider, from `HEAD` back to (but not including) a starting point.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CommitRange {
+    /// Start here instead of at the last version tag
+    pub(crate) since: Option<Since>,
+    /// Only follow the first parent of merge commits
+    pub(crate) first_parent: bool,
+    /// End at this revision (like an older tag) instead of `HEAD`
+    pub(crate) until: Option<String>,
+}
 
-    let reference = repo.find_reference(&format!("refs/tags/{tag}")).ok();
-    if reference.is_some() {
-        debug!("Using commits since tag {tag}");
+/// The message of a commit, and whether it changed any files matching the requested paths.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct CommitMessage {
+    /// The full hex ID of the commit
+    pub(crate) id: String,
+    pub(crate) message: String,
+    pub(crate) author: Author,
+    pub(crate) touches_paths: bool,
+    /// Whether the commit has more than one parent
+    pub(crate) is_merge: bool,
+}
+
+/// Get every commit since `tag` (or `range.since`), diffing each against its first parent to check
+/// whether it changed any files matching `paths`.
+pub(crate) fn get_commits_after_tag(
+    tag: &str,
+    range: &CommitRange,
+    paths: &[Pattern],
+) -> Result<Vec<CommitMessage>, Error> {
+    let repo = gix::open(".")?;
+    let start = if let Some(since) = &range.since {
+        Some(since.resolve()?)
     } else {
-        debug!("Tag {tag} not found, using ALL commits");
+        let reference = repo.find_reference(&format!("refs/tags/{tag}")).ok();
+        if reference.is_some() {
+            debug!("Using commits since tag {tag}");
+        } else {
+            debug!("Tag {tag} not found, using ALL commits");
+        }
+        reference
+            .map(|reference| reference.into_fully_peeled_id().map(gix::Id::detach))
+            .transpose()?
+    };
+    let mut commits = commits_in_range(&repo, start, range)?;
+    if !paths.is_empty() {
+        let diff_repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+        for commit in &mut commits {
+            let files = changed_files(&diff_repo, &commit.id)?;
+            commit.touches_paths = matches_any(&files, paths);
+        }
+    }
+    Ok(commits)
+}
+
+/// A commit along with every file it changed compared to its first parent.
+#[derive(Clone, Debug)]
+pub(crate) struct ChangedCommit {
+    commit: CommitMessage,
+    files: Vec<PathBuf>,
+}
+
+impl ChangedCommit {
+    /// The commit, with [`CommitMessage::touches_paths`] set for `paths`.
+    pub(crate) fn for_paths(&self, paths: &[Pattern]) -> CommitMessage {
+        CommitMessage {
+            touches_paths: matches_any(&self.files, paths),
+            ..self.commit.clone()
+        }
     }
-    let commits_to_exclude = reference
-        .map(gix::Reference::into_fully_peeled_id)
-        .transpose()?
-        .and_then(|tag_oid| repo.find_object(tag_oid).ok().map(gix::Object::into_commit))
+}
+
+/// Get every commit since `since` along with the files it changed, walking the history only once
+/// for any number of path filters.
+pub(crate) fn get_changed_commits(since: &Since) -> Result<Vec<ChangedCommit>, Error> {
+    let repo = gix::open(".")?;
+    let diff_repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+    commits_in_range(&repo, Some(since.resolve()?), &CommitRange::default())?
+        .into_iter()
+        .map(|commit| {
+            Ok(ChangedCommit {
+                files: changed_files(&diff_repo, &commit.id)?,
+                commit,
+            })
+        })
+        .collect()
+}
+
+/// Every commit from `range.until` (or `HEAD`) which isn't reachable from `start`, oldest first.
+///
+/// This builds a complete set of every commit reachable from `start`, because branching and
+/// merging means that there could be paths which jump _behind_ it... and we want to exclude those
+/// as well.
+fn commits_in_range(
+    repo: &gix::Repository,
+    start: Option<ObjectId>,
+    range: &CommitRange,
+) -> Result<Vec<CommitMessage>, Error> {
+    let commits_to_exclude = start
+        .and_then(|start| repo.find_object(start).ok().map(gix::Object::into_commit))
         .and_then(|commit| {
             commit.ancestors().all().ok().map(|ancestors| {
                 ancestors
@@ -408,25 +620,264 @@ pub(crate) fn get_commit_messages_after_tag(tag: &str) -> Result<Vec<String>, Er
             })
         })
         .unwrap_or_default();
-    let head_commit = repo.head_commit()?;
-    let mut reverse_commits = head_commit
-        .ancestors()
+    let end_commit = match &range.until {
+        Some(until) => {
+            let git2_repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+            let id = to_object_id(find_commit(&git2_repo, until)?)?;
+            repo.find_commit(id)?
+        }
+        None => repo.head_commit()?,
+    };
+    let mut ancestors = end_commit.ancestors();
+    if range.first_parent {
+        ancestors = ancestors.first_parent_only();
+    }
+    let mut reverse_commits = ancestors
         .all()?
         .filter_map(Result::ok)
         .filter(|info| !commits_to_exclude.contains(&info.id))
         .filter_map(|info| {
             info.object().ok().and_then(|commit| {
-                commit
-                    .decode()
-                    .ok()
-                    .map(|commit| commit.message.to_string())
+                commit.decode().ok().map(|commit| CommitMessage {
+                    id: info.id.to_string(),
+                    message: commit.message.to_string(),
+                    author: Author {
+                        name: commit.author.name.to_string(),
+                        email: commit.author.email.to_string(),
+                        login: None,
+                    },
+                    touches_paths: false,
+                    is_merge: commit.parents().nth(1).is_some(),
+                })
             })
         })
-        .collect_vec();
+        .collect::<Vec<_>>();
     reverse_commits.reverse();
     Ok(reverse_commits)
 }
 
+/// Every file the commit `id` changed compared to its first parent.
+fn changed_files(repo: &Repository, id: &str) -> Result<Vec<PathBuf>, Error> {
+    let commit = repo.find_commit(git2::Oid::from_str(id)?)?;
+    let tree = commit.tree()?;
+    let parent_tree = commit
+        .parents()
+        .next()
+        .map(|parent| parent.tree())
+        .transpose()?;
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+    Ok(diff
+        .deltas()
+        .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+        .flatten()
+        .map(Path::to_path_buf)
+        .collect())
+}
+
+/// Whether any of `files` matches any of `paths`.
+fn matches_any(files: &[PathBuf], paths: &[Pattern]) -> bool {
+    files
+        .iter()
+        .any(|file| paths.iter().any(|pattern| pattern.matches_path(file)))
+}
+
+/// The commit which added a file, and who wrote it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct AddedBy {
+    /// The full hex ID of the commit
+    pub(crate) id: String,
+    pub(crate) author: Author,
+}
+
+/// Find the most recent commit (reachable from `HEAD`, but not from `last_release`) which added
+/// each of `paths`.
+///
+/// Merge commits are skipped, so a file added on a merged branch is credited to the commit on that
+/// branch. Paths which were never committed are left out of the result.
+pub(crate) fn find_adding_commits(
+    paths: &[RelativePathBuf],
+    last_release: &str,
+) -> Result<HashMap<RelativePathBuf, AddedBy>, Error> {
+    let repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+    adding_commits(&repo, paths, last_release)
+}
+
+fn adding_commits(
+    repo: &Repository,
+    paths: &[RelativePathBuf],
+    last_release: &str,
+) -> Result<HashMap<RelativePathBuf, AddedBy>, Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    revwalk.push_head()?;
+    if let Ok(tag) = repo.revparse_single(&format!("refs/tags/{last_release}")) {
+        debug!("Looking for change files added since {last_release}");
+        revwalk.hide(tag.peel_to_commit()?.id())?;
+    }
+    let mut options = git2::DiffOptions::new();
+    options.disable_pathspec_match(true);
+    for path in paths {
+        options.pathspec(path.as_str());
+    }
+    let mut found = HashMap::new();
+    for id in revwalk {
+        if found.len() == paths.len() {
+            break;
+        }
+        let commit = repo.find_commit(id?)?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        let tree = commit.tree()?;
+        let parent_tree = commit
+            .parents()
+            .next()
+            .map(|parent| parent.tree())
+            .transpose()?;
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut options))?;
+        for delta in diff.deltas() {
+            if delta.status() != Delta::Added {
+                continue;
+            }
+            let Some(path) = delta
+                .new_file()
+                .path()
+                .and_then(|path| RelativePathBuf::from_path(path).ok())
+            else {
+                continue;
+            };
+            if paths.contains(&path) && !found.contains_key(&path) {
+                let signature = commit.author();
+                let author = Author {
+                    name: signature.name().unwrap_or_default().to_string(),
+                    email: signature.email().unwrap_or_default().to_string(),
+                    login: None,
+                };
+                found.insert(
+                    path,
+                    AddedBy {
+                        id: commit.id().to_string(),
+                        author,
+                    },
+                );
+            }
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_adding_commits {
+    use git2::{Commit, Oid, Signature};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn commit(repo: &Repository, author: &str, file: &str, parents: &[&Commit]) -> Oid {
+        let mut tree = repo
+            .treebuilder(
+                parents
+                    .first()
+                    .map(|parent| parent.tree().unwrap())
+                    .as_ref(),
+            )
+            .unwrap();
+        for parent in parents.iter().skip(1) {
+            for entry in &parent.tree().unwrap() {
+                tree.insert(entry.name().unwrap(), entry.id(), entry.filemode())
+                    .unwrap();
+            }
+        }
+        tree.insert(file, repo.blob(file.as_bytes()).unwrap(), 0o100_644)
+            .unwrap();
+        let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+        let signature = Signature::now(author, &format!("{author}@example.com")).unwrap();
+        repo.commit(None, &signature, &signature, author, &tree, parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn merged_branch_since_last_release() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let released = commit(&repo, "alice", "released.md", &[]);
+        let released = repo.find_commit(released).unwrap();
+        repo.tag_lightweight("v1.0.0", released.as_object(), false)
+            .unwrap();
+        let branch = commit(&repo, "bob", "from_branch.md", &[&released]);
+        let main = commit(&repo, "carol", "from_main.md", &[&released]);
+        let merge = commit(
+            &repo,
+            "dave",
+            "merged.md",
+            &[
+                &repo.find_commit(main).unwrap(),
+                &repo.find_commit(branch).unwrap(),
+            ],
+        );
+        repo.set_head_detached(merge).unwrap();
+
+        let paths = [
+            "released.md",
+            "from_branch.md",
+            "from_main.md",
+            "uncommitted.md",
+        ]
+        .map(RelativePathBuf::from);
+        let found = adding_commits(&repo, &paths, "v1.0.0").unwrap();
+
+        let authors: HashMap<&str, &str> = found
+            .iter()
+            .map(|(path, added_by)| (path.as_str(), added_by.author.name.as_str()))
+            .collect();
+        assert_eq!(
+            authors,
+            HashMap::from([("from_branch.md", "bob"), ("from_main.md", "carol")])
+        );
+        assert_eq!(
+            found
+                .get(&RelativePathBuf::from("from_branch.md"))
+                .unwrap()
+                .id,
+            branch.to_string()
+        );
+    }
+}
+
+/// Files under `directory` which were added on the current branch since it split from `base`
+/// (like in a pull request against `base`).
+pub(crate) fn files_added_since(
+    base: &str,
+    directory: &str,
+) -> Result<Vec<RelativePathBuf>, Error> {
+    let repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+    let head = find_commit(&repo, "HEAD")?;
+    let merge_base = repo.merge_base(head, find_commit(&repo, base)?)?;
+    let old_tree = repo.find_commit(merge_base)?.tree()?;
+    let new_tree = repo.find_commit(head)?.tree()?;
+    let mut options = git2::DiffOptions::new();
+    options.pathspec(directory);
+    let diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut options))?;
+    Ok(diff
+        .deltas()
+        .filter(|delta| delta.status() == Delta::Added)
+        .filter_map(|delta| {
+            delta
+                .new_file()
+                .path()
+                .and_then(|path| RelativePathBuf::from_path(path).ok())
+        })
+        .collect())
+}
+
+/// The abbreviated (7 character) ID of the `HEAD` commit.
+pub(crate) fn short_head_sha() -> Result<String, Error> {
+    let repo = gix::open(current_dir().map_err(ErrorKind::CurrentDirectory)?)?;
+    let head = repo.head_commit()?;
+    Ok(head.id.to_hex_with_len(7).to_string())
+}
+
 pub(crate) fn create_tag(name: RunType<&str>) -> Result<(), Error> {
     match name {
         RunType::DryRun(name) => {
@@ -0,0 +1,82 @@
+use miette::Diagnostic;
+
+use super::initialize_state;
+use crate::{
+    app_config, config,
+    integrations::{known_commit, CommitResponse},
+    state,
+};
+
+/// Look up the Gitea username of whoever authored a commit, if Gitea can match it to an account.
+///
+/// Commits which Gitea doesn't have, like ones which were never pushed, have no username.
+pub(crate) fn get_commit_author(
+    sha: &str,
+    gitea_state: state::Gitea,
+    gitea_config: &config::Gitea,
+) -> Result<(state::Gitea, Option<String>), Error> {
+    let (token, agent) = initialize_state(&gitea_config.host, gitea_state)?;
+    let url = format!(
+        "{host}/api/v1/repos/{owner}/{repo}/git/commits/{sha}",
+        host = gitea_config.host,
+        owner = gitea_config.owner,
+        repo = gitea_config.repo,
+    );
+    let activity = format!("looking up the author of commit {sha}");
+    let response = known_commit(
+        agent
+            .get(&url)
+            .set("Accept", "application/json")
+            .query("access_token", &token)
+            .call(),
+    )
+    .map_err(|err| Error::ApiRequest {
+        err,
+        activity: activity.clone(),
+        host: gitea_config.host.clone(),
+    })?;
+    let login = match response {
+        Some(response) => response
+            .into_json::<CommitResponse>()
+            .map_err(|source| Error::ApiResponse {
+                source,
+                activity,
+                host: gitea_config.host.clone(),
+            })?
+            .author
+            .map(|author| author.login),
+        None => None,
+    };
+    Ok((state::Gitea::Initialized { token, agent }, login))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Trouble communicating with the Gitea instance while {activity}: {err}")]
+    #[diagnostic(
+        code(gitea::api_request_error),
+        help(
+            "There was a problem communicating with the Gitea instance {host}, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest {
+        err: String,
+        activity: String,
+        host: String,
+    },
+    #[error("Trouble decoding the response from Gitea while {activity}: {source}")]
+    #[diagnostic(
+        code(gitea::api_response_error),
+        help(
+            "Failure to decode a response from the Gitea instance at {host} is probably a bug. Please report it at https://github.com/knope-dev/knope"
+        )
+    )]
+    ApiResponse {
+        source: std::io::Error,
+        activity: String,
+        host: String,
+    },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+}
@@ -0,0 +1,53 @@
+use miette::Diagnostic;
+
+use super::initialize_state;
+use crate::{app_config, config, integrations::ureq_err_to_string, state};
+
+/// Check whether the Gitea instance already has a release for `tag_name`.
+pub(crate) fn release_exists(
+    tag_name: &str,
+    gitea_state: state::Gitea,
+    gitea_config: &config::Gitea,
+) -> Result<(state::Gitea, bool), Error> {
+    let (token, agent) = initialize_state(&gitea_config.host, gitea_state)?;
+    let url = format!(
+        "{releases}/tags/{tag_name}",
+        releases = gitea_config.get_releases_url()
+    );
+    let exists = match agent
+        .get(&url)
+        .set("Accept", "application/json")
+        .query("access_token", &token)
+        .call()
+    {
+        Ok(_) => true,
+        Err(ureq::Error::Status(404, _)) => false,
+        Err(source) => {
+            return Err(Error::ApiRequest {
+                err: ureq_err_to_string(source),
+                activity: format!("looking for the release of {tag_name}"),
+                host: gitea_config.host.clone(),
+            })
+        }
+    };
+    Ok((state::Gitea::Initialized { token, agent }, exists))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Trouble communicating with the Gitea instance while {activity}: {err}")]
+    #[diagnostic(
+        code(gitea::api_request_error),
+        help(
+            "There was a problem communicating with the Gitea instance {host}, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest {
+        err: String,
+        activity: String,
+        host: String,
+    },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+}
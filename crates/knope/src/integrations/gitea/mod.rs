@@ -7,12 +7,16 @@ use crate::{
 
 pub(crate) mod create_pull_request;
 pub(crate) mod create_release;
+mod get_commit_author;
+mod get_release;
 mod list_issues;
 
 pub(crate) use create_pull_request::{
     create_or_update_pull_request, Error as CreatePullRequestError,
 };
 pub(crate) use create_release::{create_release, Error as CreateReleaseError};
+pub(crate) use get_commit_author::{get_commit_author, Error as GetCommitAuthorError};
+pub(crate) use get_release::{release_exists, Error as GetReleaseError};
 pub(crate) use list_issues::{list_issues, Error as ListIssuesError};
 
 fn initialize_state(host: &str, state: state::Gitea) -> Result<(String, Agent), app_config::Error> {
@@ -0,0 +1,70 @@
+use miette::Diagnostic;
+
+use super::initialize_state;
+use crate::{
+    app_config, config,
+    integrations::{known_commit, CommitResponse},
+    state,
+};
+
+/// Look up the GitHub username of whoever authored a commit, if GitHub can match it to an account.
+///
+/// Commits which GitHub doesn't have, like ones which were never pushed, have no username.
+pub(crate) fn get_commit_author(
+    sha: &str,
+    github_state: state::GitHub,
+    github_config: &config::GitHub,
+) -> Result<(state::GitHub, Option<String>), Error> {
+    let (token, agent) = initialize_state(github_state)?;
+    let url = format!(
+        "https://api.github.com/repos/{owner}/{repo}/commits/{sha}",
+        owner = github_config.owner,
+        repo = github_config.repo,
+    );
+    let activity = format!("looking up the author of commit {sha}");
+    let response = known_commit(
+        agent
+            .get(&url)
+            .set("Authorization", &format!("token {token}"))
+            .call(),
+    )
+    .map_err(|err| Error::ApiRequest {
+        err,
+        activity: activity.clone(),
+    })?;
+    let login = match response {
+        Some(response) => response
+            .into_json::<CommitResponse>()
+            .map_err(|source| Error::ApiResponse {
+                message: source.to_string(),
+                activity,
+            })?
+            .author
+            .map(|author| author.login),
+        None => None,
+    };
+    Ok((state::GitHub::Initialized { token, agent }, login))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+    #[error("Trouble communicating with GitHub while {activity}: {err}")]
+    #[diagnostic(
+        code(github::api_request_error),
+        help(
+            "There was a problem communicating with GitHub, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest { err: String, activity: String },
+    #[error("Trouble decoding the response from GitHub while {activity}: {message}")]
+    #[diagnostic(
+        code(github::api_response_error),
+        help(
+            "Failure to decode a response from GitHub is probably a bug. Please report it at https://github.com/knope-dev/knope"
+        )
+    )]
+    ApiResponse { message: String, activity: String },
+}
@@ -0,0 +1,48 @@
+use miette::Diagnostic;
+
+use super::initialize_state;
+use crate::{app_config, config, integrations::ureq_err_to_string, state};
+
+/// Check whether GitHub already has a release for `tag_name`.
+pub(crate) fn release_exists(
+    tag_name: &str,
+    github_state: state::GitHub,
+    github_config: &config::GitHub,
+) -> Result<(state::GitHub, bool), Error> {
+    let (token, agent) = initialize_state(github_state)?;
+    let url = format!(
+        "https://api.github.com/repos/{owner}/{repo}/releases/tags/{tag_name}",
+        owner = github_config.owner,
+        repo = github_config.repo,
+    );
+    let exists = match agent
+        .get(&url)
+        .set("Authorization", &format!("token {token}"))
+        .call()
+    {
+        Ok(_) => true,
+        Err(ureq::Error::Status(404, _)) => false,
+        Err(source) => {
+            return Err(Error::ApiRequest {
+                err: ureq_err_to_string(source),
+                activity: format!("looking for the release of {tag_name}"),
+            })
+        }
+    };
+    Ok((state::GitHub::Initialized { token, agent }, exists))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+    #[error("Trouble communicating with GitHub while {activity}: {err}")]
+    #[diagnostic(
+        code(github::api_request_error),
+        help(
+            "There was a problem communicating with GitHub, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest { err: String, activity: String },
+}
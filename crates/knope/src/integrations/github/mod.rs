@@ -2,12 +2,16 @@ pub(crate) use create_pull_request::{
     create_or_update_pull_request, Error as CreatePullRequestError,
 };
 pub(crate) use create_release::{create_release, Error as CreateReleaseError};
+pub(crate) use get_commit_author::{get_commit_author, Error as GetCommitAuthorError};
+pub(crate) use get_release::{release_exists, Error as GetReleaseError};
 use ureq::Agent;
 
 use crate::{app_config, app_config::get_or_prompt_for_github_token, state};
 
 mod create_pull_request;
 mod create_release;
+mod get_commit_author;
+mod get_release;
 
 fn initialize_state(state: state::GitHub) -> Result<(String, Agent), app_config::Error> {
     Ok(match state {
@@ -44,6 +44,30 @@ struct CreateReleaseResponse {
     upload_url: String,
 }
 
+/// The parts of a commit from the GitHub or Gitea API that Knope uses.
+#[derive(Deserialize)]
+struct CommitResponse {
+    /// The forge account matching the commit author, if any
+    author: Option<CommitUser>,
+}
+
+#[derive(Deserialize)]
+struct CommitUser {
+    login: String,
+}
+
+/// Forges answer with 404 for commits they don't have (like ones which were never pushed) and 422
+/// for commits they can't look up, neither of which should stop a release, so both are `None`.
+fn known_commit(
+    result: Result<ureq::Response, ureq::Error>,
+) -> Result<Option<ureq::Response>, String> {
+    match result {
+        Ok(response) => Ok(Some(response)),
+        Err(ureq::Error::Status(404 | 422, _)) => Ok(None),
+        Err(err) => Err(ureq_err_to_string(err)),
+    }
+}
+
 #[derive(serde::Deserialize)]
 struct ResponseIssue {
     number: usize,
@@ -58,3 +82,25 @@ fn ureq_err_to_string(err: ureq::Error) -> String {
         ureq::Error::Transport(err) => format!("Transport error: {err}"),
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_known_commit {
+    use super::*;
+
+    fn status(code: u16) -> ureq::Error {
+        ureq::Error::Status(code, ureq::Response::new(code, "", "{}").unwrap())
+    }
+
+    #[test]
+    fn missing_commit() {
+        assert!(known_commit(Err(status(404))).unwrap().is_none());
+        assert!(known_commit(Err(status(422))).unwrap().is_none());
+    }
+
+    #[test]
+    fn other_errors() {
+        assert!(known_commit(Err(status(401))).is_err());
+        assert!(known_commit(Err(status(500))).is_err());
+    }
+}
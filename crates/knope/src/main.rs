@@ -1,9 +1,15 @@
 use std::str::FromStr;
 
 use clap::{arg, command, value_parser, Arg, ArgAction, ArgMatches, Command};
+use indexmap::IndexMap;
 use itertools::Itertools;
-use knope_versioning::{package, semver::Version};
+use knope_versioning::{
+    changes::CHANGESET_DIR,
+    package,
+    semver::{BuildMetadata, Version},
+};
 use miette::{miette, Result};
+use relative_path::RelativePathBuf;
 use tracing::info;
 use tracing_subscriber::{
     filter::{filter_fn, LevelFilter},
@@ -14,9 +20,10 @@ use tracing_subscriber::{
 
 use crate::{
     config::{Config, ConfigSource},
-    integrations::git::all_tags_on_branch,
+    integrations::git::{all_tags_on_branch, Since},
     state::{RunType, State},
-    step::{releases::Package, Step},
+    step::{releases::Package, CreateChangeFile, Step},
+    variables::Template,
     workflow::Workflow,
 };
 
@@ -97,6 +104,28 @@ pub fn main() -> Result<()> {
                 config.set_prerelease_label(prerelease_label);
             })
     });
+    sub_matches.as_ref().and_then(|matches| {
+        matches
+            .try_get_one::<Since>(SINCE)
+            .ok()
+            .flatten()
+            .map(|since| {
+                config.set_since(since);
+            })
+    });
+    sub_matches.as_ref().and_then(|matches| {
+        matches
+            .try_get_one::<BuildMetadata>(BUILD_METADATA)
+            .ok()
+            .flatten()
+            .map(|build_metadata| {
+                config.set_build_metadata(build_metadata);
+            })
+    });
+
+    if let Some(inputs) = sub_matches.as_ref().map(change_file_inputs) {
+        config.set_change_file_inputs(&inputs);
+    }
 
     let (state, workflows) = create_state(config, sub_matches.as_mut())?;
 
@@ -126,7 +155,42 @@ pub fn main() -> Result<()> {
 const OVERRIDE_ONE_VERSION: &str = "override-one-version";
 const OVERRIDE_MULTIPLE_VERSIONS: &str = "override-multiple-versions";
 const PRERELEASE_LABEL: &str = "prerelease-label";
+const BUILD_METADATA: &str = "build-metadata";
+const SINCE: &str = "since";
 const VERBOSE: &str = "verbose";
+const PACKAGE: &str = "package";
+const CHANGE_TYPE: &str = "change-type";
+const SUMMARY: &str = "summary";
+const BODY: &str = "body";
+const EDITOR: &str = "editor";
+
+/// The inputs for `CreateChangeFile` steps which were passed on the command line.
+fn change_file_inputs(matches: &ArgMatches) -> CreateChangeFile {
+    let get = |id: &str| matches.try_get_one::<String>(id).ok().flatten().cloned();
+    let template = |template| Template {
+        template,
+        variables: IndexMap::new(),
+    };
+    CreateChangeFile {
+        packages: matches
+            .try_get_many::<String>(PACKAGE)
+            .ok()
+            .flatten()
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect(),
+        change_type: get(CHANGE_TYPE),
+        summary: get(SUMMARY).map(template),
+        body: get(BODY).map(template),
+        editor: matches
+            .try_get_one::<bool>(EDITOR)
+            .ok()
+            .flatten()
+            .copied()
+            .unwrap_or_default(),
+    }
+}
 
 fn build_cli(config: &ConfigSource) -> Command {
     let mut command = command!()
@@ -196,14 +260,71 @@ fn build_cli(config: &ConfigSource) -> Command {
                         .long("prerelease-label")
                         .help("Set the `prerelease_label` attribute of any `PrepareRelease` steps at runtime.")
                         .env("KNOPE_PRERELEASE_LABEL")
+                )
+                .arg(
+                    Arg::new(SINCE)
+                        .long(SINCE)
+                        .help("Set the `since` attribute of any `PrepareRelease` steps at runtime, like `v1.2.0` or `merge-base:main`.")
+                        .env("KNOPE_SINCE")
+                        .value_parser(value_parser!(Since)),
                 );
         }
+        if workflow
+            .steps
+            .iter()
+            .any(|step| matches!(*step, Step::CreateChangeFile(_)))
+        {
+            subcommand = change_file_args(subcommand);
+        }
+        if contains_bump_version || contains_prepare_release {
+            subcommand = subcommand.arg(
+                Arg::new(BUILD_METADATA)
+                    .long(BUILD_METADATA)
+                    .help("Attach build metadata (like a commit SHA) to versions set by `BumpVersion` or `PrepareRelease`.")
+                    .env("KNOPE_BUILD_METADATA")
+                    .value_parser(value_parser!(BuildMetadata)),
+            );
+        }
 
         command = command.subcommand(subcommand);
     }
     command
 }
 
+/// Arguments to set the inputs of `CreateChangeFile` steps instead of prompting for them.
+fn change_file_args(subcommand: Command) -> Command {
+    subcommand
+        .arg(
+            Arg::new(PACKAGE)
+                .long(PACKAGE)
+                .help(
+                    "Set the packages of any `CreateChangeFile` steps, can be set multiple times.",
+                )
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new(CHANGE_TYPE)
+                .long(CHANGE_TYPE)
+                .help("Set the change type of any `CreateChangeFile` steps, like `minor`."),
+        )
+        .arg(
+            Arg::new(SUMMARY)
+                .long(SUMMARY)
+                .help("Set the summary of any `CreateChangeFile` steps."),
+        )
+        .arg(
+            Arg::new(BODY)
+                .long(BODY)
+                .help("Set the body of any `CreateChangeFile` steps."),
+        )
+        .arg(
+            Arg::new(EDITOR)
+                .long(EDITOR)
+                .help("Write the summary and body of any `CreateChangeFile` steps in `$VISUAL` or `$EDITOR`.")
+                .action(ArgAction::SetTrue),
+        )
+}
+
 fn create_state(
     config: Config,
     mut sub_matches: Option<&mut ArgMatches>,
@@ -214,14 +335,30 @@ fn create_state(
         jira,
         github,
         gitea,
+        changeset_directory,
     } = config;
+    let changeset_directory = changeset_directory.map_or_else(
+        || RelativePathBuf::from(CHANGESET_DIR),
+        |path| path.normalize(),
+    );
     let git_tags = if packages.is_empty() {
         // Don't mess with Git if there aren't any packages defined
         Vec::new()
     } else {
         all_tags_on_branch().unwrap_or_default()
     };
-    let (mut packages, versioned_files) = Package::load(packages, &git_tags)?;
+    let forge = github
+        .as_ref()
+        .map(config::GitHub::forge)
+        .or_else(|| gitea.as_ref().map(config::Gitea::forge));
+    let issue_tracker = jira.as_ref().map(config::Jira::issue_tracker);
+    let (mut packages, versioned_files) = Package::load(
+        packages,
+        &git_tags,
+        forge.as_ref(),
+        issue_tracker.as_ref(),
+        &changeset_directory,
+    )?;
     if let Some(version_override) = sub_matches
         .as_deref_mut()
         .and_then(|matches| matches.try_remove_one::<Version>(OVERRIDE_ONE_VERSION).ok())
@@ -265,7 +402,8 @@ fn create_state(
         }
     }
 
-    let state = State::new(jira, github, gitea, packages, versioned_files, git_tags);
+    let mut state = State::new(jira, github, gitea, packages, versioned_files, git_tags);
+    state.changeset_directory = changeset_directory;
     Ok((state, workflows))
 }
 
@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 
-use knope_versioning::{Action, VersionedFile};
+use knope_versioning::{changes::CHANGESET_DIR, Action, VersionedFile};
+use relative_path::RelativePathBuf;
 
 use crate::{
     config,
@@ -20,6 +21,8 @@ pub(crate) struct State {
     pub(crate) all_versioned_files: Vec<VersionedFile>,
     pub(crate) pending_actions: Vec<Action>,
     pub(crate) all_git_tags: Vec<String>,
+    /// Where change files are, unless a package has its own directory
+    pub(crate) changeset_directory: RelativePathBuf,
 }
 
 impl State {
@@ -43,6 +46,7 @@ impl State {
             all_versioned_files,
             all_git_tags,
             pending_actions: Vec::new(),
+            changeset_directory: RelativePathBuf::from(CHANGESET_DIR),
         }
     }
 }
@@ -0,0 +1,99 @@
+use itertools::Itertools;
+use miette::Diagnostic;
+use tracing::debug;
+
+use super::releases::{change_files, conventional_commits::conventional_commits_for};
+use crate::{
+    integrations::git::{self, files_added_since, get_changed_commits, Since},
+    state::{RunType, State},
+};
+
+/// Check that every package with changes between `base` and `HEAD` documents them, either with
+/// a change file added since `base` or with a conventional commit.
+pub(super) fn run(base: &str, state: RunType<State>) -> Result<RunType<State>, Error> {
+    let (run_type, state) = state.take();
+    if state.packages.is_empty() {
+        return Err(Error::NoDefinedPackages);
+    }
+
+    let added = change_files::directories(&state.changeset_directory, &state.packages)
+        .into_iter()
+        .map(|directory| {
+            let paths = files_added_since(base, directory.as_str())?
+                .into_iter()
+                .filter(|path| {
+                    path.extension() == Some("md")
+                        && path
+                            .parent()
+                            .is_some_and(|parent| parent.normalize() == directory.normalize())
+                })
+                .map(|path| path.to_path(""))
+                .filter(|path| path.exists())
+                .collect();
+            Ok((directory, paths))
+        })
+        .collect::<Result<Vec<_>, git::Error>>()?;
+    let change_files = change_files::parse_files(added, &state.packages)?;
+
+    let commits = get_changed_commits(&Since::Revision(base.to_string()))?;
+    let mut undocumented = Vec::new();
+    for package in &state.packages {
+        let paths = package.paths();
+        let commits = commits
+            .iter()
+            .map(|commit| commit.for_paths(paths))
+            .collect::<Vec<_>>();
+        let changed = commits
+            .iter()
+            .any(|commit| !commit.is_merge && (paths.is_empty() || commit.touches_paths));
+        if !changed {
+            debug!("Package {} has no changes since {base}", package.name());
+            continue;
+        }
+        let commits = conventional_commits_for(
+            package.path_filter.as_ref(),
+            &package.changeset_directory,
+            commits,
+        );
+        if package
+            .versioning
+            .get_changes(&change_files, &commits, false)
+            .is_empty()
+        {
+            undocumented.push(package.name().to_string());
+        }
+    }
+
+    if undocumented.is_empty() {
+        Ok(run_type.of(state))
+    } else {
+        Err(Error::MissingChanges {
+            base: base.to_string(),
+            packages: undocumented,
+        })
+    }
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ChangeFiles(#[from] change_files::Error),
+    #[error("No packages are defined")]
+    #[diagnostic(
+        code(check_changes::no_defined_packages),
+        help("You must define at least one package to check for changes"),
+        url("https://knope.tech/reference/config-file/packages/")
+    )]
+    NoDefinedPackages,
+    #[error("Packages changed since {base} without a change file or conventional commit: {}", packages.iter().join(", "))]
+    #[diagnostic(
+        code(check_changes::missing_changes),
+        help("Add a change file (with `knope document-change`) or a conventional commit for each package."),
+        url("https://knope.tech/reference/config-file/steps/check-changes/")
+    )]
+    MissingChanges { base: String, packages: Vec<String> },
+}
@@ -0,0 +1,138 @@
+use knope_versioning::{
+    changes::conventional_commit::{check_commit, CommitProblem},
+    release_notes::Sections,
+};
+use miette::{Diagnostic, LabeledSpan};
+use tracing::debug;
+
+use crate::{
+    integrations::git::{self, get_commits_after_tag, CommitMessage, CommitRange, Since},
+    state::{RunType, State},
+};
+
+/// Check that every commit between `base` and `HEAD` is a conventional commit that Knope
+/// understands, using the scopes and changelog sections of all packages.
+///
+/// Merge commits and commits that any package ignores are skipped.
+pub(super) fn run(base: &str, state: RunType<State>) -> Result<RunType<State>, Error> {
+    let range = CommitRange {
+        since: Some(Since::Revision(base.to_string())),
+        ..CommitRange::default()
+    };
+    let commits = get_commits_after_tag(base, &range, &[])?;
+
+    let (run_type, state) = state.take();
+    let packages = &state.packages;
+    let configured_scopes = packages
+        .iter()
+        .filter_map(|package| {
+            package.versioning.scopes().or_else(|| {
+                package
+                    .path_filter
+                    .as_ref()
+                    .and_then(|filter| filter.scopes.as_deref())
+            })
+        })
+        .flatten()
+        .cloned()
+        .collect::<Vec<_>>();
+    let scopes = (!configured_scopes.is_empty()).then_some(configured_scopes.as_slice());
+    let default_sections = Sections::default();
+    let mut sections = packages
+        .iter()
+        .map(|package| &package.versioning.release_notes.sections)
+        .collect::<Vec<_>>();
+    if sections.is_empty() {
+        sections.push(&default_sections);
+    }
+
+    let count = commits.len();
+    let invalid = commits
+        .into_iter()
+        .filter(|commit| {
+            !commit.is_merge
+                && !packages
+                    .iter()
+                    .any(|package| package.versioning.ignores_commit(&commit.message))
+        })
+        .filter_map(|commit| {
+            let problems = check_commit(&commit.message, scopes, &sections);
+            (!problems.is_empty()).then(|| InvalidCommit::new(&commit, &problems))
+        })
+        .collect::<Vec<_>>();
+    if !invalid.is_empty() {
+        return Err(InvalidCommits {
+            base: base.to_string(),
+            commits: invalid,
+        }
+        .into());
+    }
+    debug!("All {count} commits since {base} are valid");
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidCommits(#[from] InvalidCommits),
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+#[error("Found {count} invalid commits since {base}", count = self.commits.len())]
+#[diagnostic(
+    code(check_commits::invalid_commits),
+    help("Reword these commits (for example, with `git rebase --interactive`) so that Knope can use them."),
+    url("https://knope.tech/reference/config-file/steps/check-commits/")
+)]
+pub(crate) struct InvalidCommits {
+    base: String,
+    #[related]
+    commits: Vec<InvalidCommit>,
+}
+
+/// A single commit which failed [`check_commit`], with a label for each problem.
+#[derive(Debug, Diagnostic, thiserror::Error)]
+#[error("Commit {id} is not valid")]
+#[diagnostic(code(check_commits::invalid_commit))]
+pub(crate) struct InvalidCommit {
+    id: String,
+    #[source_code]
+    message: String,
+    #[label(collection)]
+    problems: Vec<LabeledSpan>,
+}
+
+impl InvalidCommit {
+    fn new(commit: &CommitMessage, problems: &[CommitProblem]) -> Self {
+        let message = commit.message.trim().to_string();
+        let summary_len = message.lines().next().unwrap_or_default().len();
+        let problems = problems
+            .iter()
+            .map(|problem| {
+                let (offset, len) = match problem {
+                    CommitProblem::NotConventional(_) => (0, summary_len),
+                    CommitProblem::UnknownScope(scope) => message
+                        .get(..summary_len)
+                        .and_then(|summary| summary.find(&format!("({scope})")))
+                        .map_or((0, summary_len), |start| (start + 1, scope.len())),
+                    CommitProblem::UnknownFooter(token) => message
+                        .match_indices(token.as_str())
+                        .find(|(start, _)| {
+                            *start > 0 && message.get(start - 1..*start) == Some("\n")
+                        })
+                        .map_or((0, summary_len), |(start, _)| (start, token.len())),
+                };
+                LabeledSpan::new(Some(problem.to_string()), offset, len)
+            })
+            .collect();
+        Self {
+            id: commit.id.get(..7).unwrap_or(&commit.id).to_string(),
+            message,
+            problems,
+        }
+    }
+}
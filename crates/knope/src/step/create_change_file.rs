@@ -1,73 +1,98 @@
-use std::path::PathBuf;
+use std::{io::IsTerminal, path::PathBuf};
 
 use changesets::{UniqueId, Versioning};
 use inquire::{MultiSelect, Select};
 use itertools::Itertools;
-use knope_versioning::changes::{ChangeType, CHANGESET_DIR};
+use knope_versioning::package;
 use miette::Diagnostic;
 use tracing::info;
 
+use super::CreateChangeFile;
 use crate::{
     fs, prompt,
     state::{RunType, State},
+    step::releases::{change_files, Package},
+    variables,
+    variables::replace_variables,
 };
 
-pub(crate) fn run(state: RunType<State>) -> Result<RunType<State>, Error> {
-    let state = match state {
-        RunType::DryRun(state) => {
-            info!("Would create a new change file");
-            return Ok(RunType::DryRun(state));
+pub(crate) fn run(
+    options: CreateChangeFile,
+    state: RunType<State>,
+) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    let missing = options.missing(state.packages.len());
+    if !missing.is_empty() {
+        match run_type {
+            RunType::DryRun(()) => {
+                info!("Would create a new change file");
+                return Ok(run_type.of(state));
+            }
+            RunType::Real(()) if !is_interactive() => {
+                return Err(Error::NotInteractive {
+                    missing: missing.join(", "),
+                });
+            }
+            RunType::Real(()) => {}
         }
-        RunType::Real(state) => state,
-    };
+    }
 
-    let packages = if state.packages.len() == 1 {
-        state.packages.iter().collect()
-    } else {
-        MultiSelect::new(
-            "Which packages does this change affect?",
-            state.packages.iter().collect(),
-        )
-        .prompt()
-        .map_err(prompt::Error::from)?
-    };
+    let CreateChangeFile {
+        packages: package_names,
+        change_type,
+        summary,
+        body,
+        editor,
+    } = options;
+    let summary = summary
+        .map(|summary| replace_variables(summary, &mut state))
+        .transpose()?;
+    let body = body
+        .map(|body| replace_variables(body, &mut state))
+        .transpose()?;
 
+    let packages = select_packages(&state.packages, &package_names)?;
     let versioning = packages
         .iter()
-        .map(|package| {
-            let package_name = package.name();
-            let change_types = package
-                .versioning
-                .release_notes
-                .sections
-                .iter()
-                .flat_map(|(_, sources)| sources.iter().filter_map(ChangeType::to_changeset_type))
-                .collect_vec();
-            let prompt = if let Some(package_name) = package_name.as_custom() {
-                format!("What type of change is this for {package_name}?")
-            } else {
-                "What type of change is this?".to_string()
-            };
-            Select::new(&prompt, change_types)
-                .prompt()
-                .map_err(prompt::Error::from)
-                .map_err(Error::from)
-                .map(|change_type| (package_name.to_string(), change_type))
-        })
+        .map(|package| select_change_type(package, change_type.as_deref()))
         .collect::<Result<Versioning, Error>>()?;
-    let summary = inquire::Text::new("What is a short summary of this change?")
-        .with_help_message("This will be used as a header in the changelog")
-        .prompt()
-        .map_err(prompt::Error::from)?;
+    let changeset_path =
+        change_files::directory_for(&state.changeset_directory, &packages).to_path("");
+    let (summary, body) = if editor {
+        if let RunType::DryRun(()) = run_type {
+            info!("Would open an editor to write the change file");
+            return Ok(run_type.of(state));
+        }
+        edit(summary.as_deref(), body.as_deref())?
+    } else if let Some(summary) = summary {
+        (summary, body)
+    } else {
+        let summary = inquire::Text::new("What is a short summary of this change?")
+            .with_help_message("This will be used as a header in the changelog")
+            .prompt()
+            .map_err(prompt::Error::from)?;
+        (summary, body)
+    };
     let unique_id = UniqueId::from(&summary);
-    let summary = format!("# {summary}");
+    let summary = match body {
+        Some(body) if !body.trim().is_empty() => format!("# {summary}\n\n{}", body.trim()),
+        _ => format!("# {summary}"),
+    };
     let change = changesets::Change {
         unique_id,
         versioning,
         summary,
     };
 
-    let changeset_path = PathBuf::from(CHANGESET_DIR);
+    if let RunType::DryRun(()) = run_type {
+        info!(
+            "Would create {}",
+            changeset_path
+                .join(change.unique_id.to_file_name())
+                .display()
+        );
+        return Ok(run_type.of(state));
+    }
     if !changeset_path.exists() {
         fs::create_dir(RunType::Real(&changeset_path))?;
     }
@@ -83,6 +108,150 @@ pub(crate) fn run(state: RunType<State>) -> Result<RunType<State>, Error> {
     Ok(RunType::Real(state))
 }
 
+/// The packages named in `names`, or the packages the user picks if there are none.
+fn select_packages<'a>(
+    packages: &'a [Package],
+    names: &[String],
+) -> Result<Vec<&'a Package>, Error> {
+    if names.is_empty() {
+        return if packages.len() == 1 {
+            Ok(packages.iter().collect())
+        } else {
+            MultiSelect::new(
+                "Which packages does this change affect?",
+                packages.iter().collect(),
+            )
+            .prompt()
+            .map_err(|err| prompt::Error::from(err).into())
+        };
+    }
+    names
+        .iter()
+        .map(|name| {
+            let package_name = package::Name::from(name.as_str());
+            packages
+                .iter()
+                .find(|package| *package.name() == package_name)
+                .ok_or_else(|| Error::UnknownPackage {
+                    package: name.clone(),
+                    available: packages.iter().map(Package::name).join(", "),
+                })
+        })
+        .collect()
+}
+
+/// Check `change_type` against the change types `package` has, or prompt for one if it's not set.
+fn select_change_type(
+    package: &Package,
+    change_type: Option<&str>,
+) -> Result<(String, changesets::ChangeType), Error> {
+    let package_name = package.name();
+    let change_types = package.change_file_types();
+    if let Some(change_type) = change_type {
+        let change_type = changesets::ChangeType::from(change_type);
+        return if change_types.contains(&change_type) {
+            Ok((package_name.to_string(), change_type))
+        } else {
+            Err(Error::UnknownChangeType {
+                change_type: change_type.to_string(),
+                package: package_name.to_string(),
+                available: change_types.iter().join(", "),
+            })
+        };
+    }
+    let prompt = if let Some(package_name) = package_name.as_custom() {
+        format!("What type of change is this for {package_name}?")
+    } else {
+        "What type of change is this?".to_string()
+    };
+    Select::new(&prompt, change_types)
+        .prompt()
+        .map_err(prompt::Error::from)
+        .map_err(Error::from)
+        .map(|change_type| (package_name.to_string(), change_type))
+}
+
+const EDITOR_INSTRUCTIONS: &str = "<!--
+The first line must be a header (starting with `# `) summarizing the change,
+it will become the file name and the header in the changelog.
+Anything after that is the body, which can use any Markdown.
+This comment will be removed. Leave the file empty to cancel.
+-->
+";
+
+/// Open `$VISUAL` or `$EDITOR` on a template with `summary` and `body` filled in, then return
+/// the summary and body that the user wrote.
+fn edit(summary: Option<&str>, body: Option<&str>) -> Result<(String, Option<String>), Error> {
+    let editor = ["VISUAL", "EDITOR"]
+        .into_iter()
+        .find_map(|var| {
+            std::env::var(var)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+        })
+        .ok_or(Error::NoEditor)?;
+    let file = tempfile::Builder::new()
+        .prefix("CHANGE_")
+        .suffix(".md")
+        .tempfile()
+        .map_err(|source| Error::EditorIo {
+            path: std::env::temp_dir(),
+            source,
+        })?;
+    let path = file.path().to_path_buf();
+    let template = format!(
+        "# {summary}\n\n{body}\n{EDITOR_INSTRUCTIONS}",
+        summary = summary.unwrap_or_default(),
+        body = body.unwrap_or_default().trim()
+    );
+    std::fs::write(&path, template).map_err(|source| Error::EditorIo {
+        path: path.clone(),
+        source,
+    })?;
+    let status = execute::command(&editor)
+        .arg(&path)
+        .status()
+        .map_err(|source| Error::EditorIo {
+            path: path.clone(),
+            source,
+        })?;
+    if !status.success() {
+        return Err(Error::EditorFailed { editor, status });
+    }
+    let contents = std::fs::read_to_string(&path).map_err(|source| Error::EditorIo {
+        path: path.clone(),
+        source,
+    })?;
+    parse_edited(&contents.replace(EDITOR_INSTRUCTIONS, ""))
+}
+
+/// Split what the user wrote into a summary and an optional body.
+fn parse_edited(contents: &str) -> Result<(String, Option<String>), Error> {
+    let contents = contents.trim();
+    if contents.is_empty() {
+        return Err(Error::EmptyChangeFile);
+    }
+    let (header, body) = contents.split_once('\n').unwrap_or((contents, ""));
+    let summary = header
+        .trim()
+        .strip_prefix("# ")
+        .map(str::trim)
+        .filter(|summary| !summary.is_empty())
+        .ok_or_else(|| Error::MissingSummary {
+            first_line: header.trim().to_string(),
+        })?;
+    let body = body.trim();
+    Ok((
+        summary.to_string(),
+        (!body.is_empty()).then(|| body.to_string()),
+    ))
+}
+
+/// Prompts need a person at a terminal, which CI never has.
+fn is_interactive() -> bool {
+    std::io::stdin().is_terminal() && std::env::var_os("CI").is_none()
+}
+
 #[derive(Debug, Diagnostic, thiserror::Error)]
 pub(crate) enum Error {
     #[error(transparent)]
@@ -91,4 +260,103 @@ pub(crate) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
     Prompt(#[from] prompt::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Variables(#[from] variables::Error),
+    #[error("Missing the {missing} of the change file, and there is no interactive terminal to prompt for them")]
+    #[diagnostic(
+        code(create_change_file::not_interactive),
+        help("Set these in the step config or pass them as command line arguments, like `--summary`."),
+        url("https://knope.tech/reference/config-file/steps/create-change-file/")
+    )]
+    NotInteractive { missing: String },
+    #[error("Unknown package {package}")]
+    #[diagnostic(
+        code(create_change_file::unknown_package),
+        help("The configured packages are: {available}")
+    )]
+    UnknownPackage { package: String, available: String },
+    #[error("Unknown change type {change_type} for package {package}")]
+    #[diagnostic(
+        code(create_change_file::unknown_change_type),
+        help("The change types for {package} are: {available}"),
+        url("https://knope.tech/reference/config-file/steps/create-change-file/")
+    )]
+    UnknownChangeType {
+        change_type: String,
+        package: String,
+        available: String,
+    },
+    #[error("No editor is configured")]
+    #[diagnostic(
+        code(create_change_file::no_editor),
+        help("Set the `VISUAL` or `EDITOR` environment variable to the command for your editor, like `code --wait`.")
+    )]
+    NoEditor,
+    #[error("Could not edit the change file at {path}: {source}")]
+    #[diagnostic(code(create_change_file::editor_io))]
+    EditorIo {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("The editor `{editor}` exited with {status}")]
+    #[diagnostic(
+        code(create_change_file::editor_failed),
+        help("No change file was created.")
+    )]
+    EditorFailed {
+        editor: String,
+        status: std::process::ExitStatus,
+    },
+    #[error("The change file was empty")]
+    #[diagnostic(code(create_change_file::empty), help("No change file was created."))]
+    EmptyChangeFile,
+    #[error("The change file must start with a summary header, but it starts with: {first_line}")]
+    #[diagnostic(
+        code(create_change_file::missing_summary),
+        help("Start the file with a line like `# A short summary of the change`."),
+        url("https://knope.tech/reference/concepts/change-file/")
+    )]
+    MissingSummary { first_line: String },
+}
+
+#[cfg(test)]
+mod test_parse_edited {
+    use super::*;
+
+    #[test]
+    fn summary_and_body() {
+        let (summary, body) =
+            parse_edited("# A summary\n\nSome **details**\n\n```rust\nlet x = 1;\n```\n")
+                .unwrap_or_default();
+        assert_eq!(summary, "A summary");
+        assert_eq!(
+            body.as_deref(),
+            Some("Some **details**\n\n```rust\nlet x = 1;\n```")
+        );
+    }
+
+    #[test]
+    fn summary_only() {
+        let (summary, body) = parse_edited("\n# A summary\n\n").unwrap_or_default();
+        assert_eq!(summary, "A summary");
+        assert_eq!(body, None);
+    }
+
+    #[test]
+    fn empty() {
+        assert!(matches!(parse_edited("  \n"), Err(Error::EmptyChangeFile)));
+    }
+
+    #[test]
+    fn missing_header() {
+        assert!(matches!(
+            parse_edited("Just some text\n"),
+            Err(Error::MissingSummary { .. })
+        ));
+        assert!(matches!(
+            parse_edited("# \nBody"),
+            Err(Error::MissingSummary { .. })
+        ));
+    }
 }
@@ -1,16 +1,19 @@
 use indexmap::IndexMap;
-use knope_versioning::semver::{Label, Rule};
+use knope_versioning::semver::{BuildMetadata, Label, Rule};
 use miette::Diagnostic;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    integrations::git,
+    integrations::{git, git::Since},
     prompt,
     state::{RunType, State},
+    step::releases::snapshot::Snapshot,
     variables::{Template, Variable},
 };
 
+mod check_changes;
+mod check_commits;
 pub mod command;
 pub(crate) mod create_change_file;
 mod create_pull_request;
@@ -63,7 +66,7 @@ pub(crate) enum Step {
     },
     /// Bump the version of the project in any supported formats found using a
     /// [Semantic Versioning](https://semver.org) rule.
-    BumpVersion(Rule),
+    BumpVersion(BumpVersion),
     /// Run a command in your current shell after optionally replacing some variables.
     Command {
         /// The command to run, with any variable keys you wish to replace.
@@ -85,16 +88,45 @@ pub(crate) enum Step {
     ///
     /// Requires that GitHub details be configured.
     Release,
+    /// Fill in changelog entries for every existing release tag, using the conventional commits
+    /// between each tag and the last stable release before it.
+    Backfill {
+        /// Also create any GitHub or Gitea releases that are missing for those tags.
+        #[serde(default)]
+        create_releases: bool,
+        /// If set to true, each bulleted conventional commit in the body of a squash merge is
+        /// treated as a separate commit, like in [`PrepareRelease`].
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        split_squash_commits: bool,
+    },
+    /// Turn the changes from conventional commits since the last release into change files, so
+    /// they can be reworded before `PrepareRelease`.
+    ConvertCommits,
+    /// Keep the "Unreleased" section of each package's changelog up to date with the changes that
+    /// the next release will have.
+    UpdateUnreleased,
     /// Create a new change file to be included in the next release.
     ///
-    /// This step is interactive and will prompt the user for the information needed to create the
-    /// change file. Do not try to run in a non-interactive environment.
-    CreateChangeFile,
+    /// Anything not set in the step (or on the command line) is prompted for, which fails without
+    /// an interactive terminal.
+    CreateChangeFile(CreateChangeFile),
     CreatePullRequest {
         base: String,
         title: Template,
         body: Template,
     },
+    /// Check that every commit between `base` and `HEAD` is a conventional commit using only
+    /// configured scopes and known footers.
+    CheckCommits {
+        /// The branch, tag, or commit to check commits after, like `main`.
+        base: String,
+    },
+    /// Check that every package changed between `base` and `HEAD` has a change file or
+    /// conventional commit documenting it.
+    CheckChanges {
+        /// The branch, tag, or commit to check changes after, like `main`.
+        base: String,
+    },
 }
 
 impl Step {
@@ -113,7 +145,7 @@ impl Step {
                 git::rebase_branch(&state.of(to))?;
                 state
             }
-            Step::BumpVersion(rule) => releases::bump_version(state, &rule)?,
+            Step::BumpVersion(bump_version) => releases::bump_version(state, &bump_version)?,
             Step::Command {
                 command,
                 variables,
@@ -124,10 +156,18 @@ impl Step {
             }
             Step::SelectIssueFromBranch => git::select_issue_from_current_branch(state)?,
             Step::Release => releases::release(state)?,
-            Step::CreateChangeFile => create_change_file::run(state)?,
+            Step::Backfill {
+                create_releases,
+                split_squash_commits,
+            } => releases::backfill(state, create_releases, split_squash_commits)?,
+            Step::ConvertCommits => releases::convert_commits(state)?,
+            Step::UpdateUnreleased => releases::update_unreleased(state)?,
+            Step::CreateChangeFile(options) => create_change_file::run(options, state)?,
             Step::CreatePullRequest { base, title, body } => {
                 create_pull_request::run(&base, title, body, state)?
             }
+            Step::CheckCommits { base } => check_commits::run(&base, state)?,
+            Step::CheckChanges { base } => check_changes::run(&base, state)?,
         })
     }
 
@@ -137,6 +177,33 @@ impl Step {
             prepare_release.prerelease_label = Some(Label::from(prerelease_label));
         }
     }
+
+    /// Set `since` if `self` is `PrepareRelease`.
+    pub(crate) fn set_since(&mut self, since: &Since) {
+        if let Step::PrepareRelease(prepare_release) = self {
+            prepare_release.since = Some(since.clone());
+        }
+    }
+
+    /// Override any of the inputs that are set in `inputs` if `self` is `CreateChangeFile`.
+    pub(crate) fn set_change_file_inputs(&mut self, inputs: &CreateChangeFile) {
+        if let Step::CreateChangeFile(create_change_file) = self {
+            create_change_file.override_with(inputs.clone());
+        }
+    }
+
+    /// Set `build_metadata` if `self` is `PrepareRelease` or `BumpVersion`.
+    pub(crate) fn set_build_metadata(&mut self, build_metadata: &BuildMetadata) {
+        match self {
+            Step::PrepareRelease(prepare_release) => {
+                prepare_release.build_metadata = Some(build_metadata.clone());
+            }
+            Step::BumpVersion(bump_version) => {
+                bump_version.build_metadata = Some(build_metadata.clone());
+            }
+            _ => {}
+        }
+    }
 }
 
 #[derive(Debug, Error, Diagnostic)]
@@ -168,10 +235,24 @@ pub(super) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
     CreatePullRequest(#[from] create_pull_request::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    CheckCommits(#[from] check_commits::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    CheckChanges(#[from] check_changes::Error),
+}
+
+impl Error {
+    /// Whether this came from invalid change files, which `--validate` reports separately.
+    pub(crate) fn is_from_change_files(&self) -> bool {
+        matches!(self, Self::Release(releases::Error::ChangeFiles(_)))
+    }
 }
 
 /// The inner content of a [`Step::PrepareRelease`] step.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[allow(clippy::struct_excessive_bools)] // Each is an independent option in `knope.toml`
 pub(crate) struct PrepareRelease {
     /// If set, the user wants to create a pre-release version using the selected label.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -182,4 +263,79 @@ pub(crate) struct PrepareRelease {
     /// If set to true, conventional commits are ignored
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub(crate) ignore_conventional_commits: bool,
+    /// If set to true, each bulleted conventional commit in the body of a squash merge is treated
+    /// as a separate commit.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) split_squash_commits: bool,
+    /// If set, this is attached to the new version as build metadata (after a `+`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) build_metadata: Option<BuildMetadata>,
+    /// If set, create an untagged snapshot version using this template for the pre-release
+    /// component, without updating changelogs or removing change files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) snapshot: Option<Snapshot>,
+    /// If set, look for conventional commits after this revision instead of the last stable
+    /// version tag.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) since: Option<Since>,
+    /// If set to true, only follow the first parent of merge commits when looking for commits.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) first_parent: bool,
+}
+
+/// The inner content of a [`Step::CreateChangeFile`] step. Anything left unset is prompted for.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct CreateChangeFile {
+    /// The names of the packages this change affects.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) packages: Vec<String>,
+    /// The type of change for every package, like `minor` or a custom change type.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) change_type: Option<String>,
+    /// Used as the header of the change in the changelog.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) summary: Option<Template>,
+    /// Extra Markdown to include under the summary.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) body: Option<Template>,
+    /// Open `$VISUAL` or `$EDITOR` to write the summary and body.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) editor: bool,
+}
+
+impl CreateChangeFile {
+    /// The names of the required inputs which aren't set (and so must be prompted for).
+    fn missing(&self, package_count: usize) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if self.packages.is_empty() && package_count > 1 {
+            missing.push("packages");
+        }
+        if self.change_type.is_none() {
+            missing.push("change type");
+        }
+        if self.summary.is_none() && !self.editor {
+            missing.push("summary");
+        }
+        missing
+    }
+
+    fn override_with(&mut self, other: Self) {
+        if !other.packages.is_empty() {
+            self.packages = other.packages;
+        }
+        self.change_type = other.change_type.or(self.change_type.take());
+        self.summary = other.summary.or(self.summary.take());
+        self.body = other.body.or(self.body.take());
+        self.editor |= other.editor;
+    }
+}
+
+/// The inner content of a [`Step::BumpVersion`] step.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct BumpVersion {
+    #[serde(flatten)]
+    pub(crate) rule: Rule,
+    /// If set, this is attached to the new version as build metadata (after a `+`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) build_metadata: Option<BuildMetadata>,
 }
@@ -0,0 +1,204 @@
+use knope_versioning::{changes::ChangeFiles, release_notes::Release, Action, ReleaseTag};
+use miette::Diagnostic;
+use tracing::{debug, info};
+
+use super::{
+    contributors::{self, UsernameLookup},
+    conventional_commits::{get_conventional_commits_after_last_stable_version, last_stable_tag},
+    gitea, github,
+    package::execute_prepare_actions,
+    Package,
+};
+use crate::{
+    integrations::{
+        git::{self, tag_date, CommitRange},
+        gitea as gitea_api, github as github_api,
+    },
+    state::{self, RunType, State},
+};
+
+/// Add a changelog entry for every release tag of every package which doesn't have one yet.
+///
+/// Each entry is built from the conventional commits between the tag and the last stable release
+/// tag before it (just like `PrepareRelease` would have) and is dated by the tag.
+/// If `create_releases` is set, any missing GitHub or Gitea releases for those tags are created too.
+pub(crate) fn backfill(
+    state: RunType<State>,
+    create_releases: bool,
+    split_squash_commits: bool,
+) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    if state.packages.is_empty() {
+        return Err(super::package::Error::NoDefinedPackages.into());
+    }
+
+    let mut username_lookup = UsernameLookup::new(
+        run_type,
+        (state.github_config.as_ref(), &mut state.github),
+        (state.gitea_config.as_ref(), &mut state.gitea),
+    );
+    let mut releases = Vec::new();
+    for package in &mut state.packages {
+        let actions = past_releases(
+            package,
+            &state.all_git_tags,
+            split_squash_commits,
+            &mut username_lookup,
+        )?;
+        let (write_actions, release_actions): (Vec<_>, Vec<_>) = actions
+            .into_iter()
+            .partition(|action| matches!(action, Action::WriteToFile { .. }));
+        execute_prepare_actions(run_type.of(write_actions.into_iter()), true)?;
+        releases.extend(
+            release_actions
+                .into_iter()
+                .filter_map(|action| match action {
+                    Action::CreateRelease(release) => Some(release),
+                    _ => None,
+                }),
+        );
+    }
+
+    if create_releases {
+        for release in &releases {
+            let tag = ReleaseTag::new(&release.version, &release.package_name);
+            create_missing_release(release, &tag, run_type.of(&mut state))?;
+        }
+    }
+
+    Ok(run_type.of(state))
+}
+
+/// The actions to fill in release notes for every release tag of `package`, oldest first.
+fn past_releases(
+    package: &mut Package,
+    all_tags: &[String],
+    split_squash_commits: bool,
+    username_lookup: &mut UsernameLookup,
+) -> Result<Vec<Action>, Error> {
+    let mut actions = Vec::new();
+    // Tags are ordered newest to oldest
+    for (index, tag) in all_tags.iter().enumerate().rev() {
+        let Some(version) = ReleaseTag::version(tag, package.name()) else {
+            continue;
+        };
+        debug!("Backfilling release notes for {tag}");
+        let older_tags = all_tags.get(index + 1..).unwrap_or_default();
+        let range = CommitRange {
+            until: Some(tag.clone()),
+            ..CommitRange::default()
+        };
+        let commits = get_conventional_commits_after_last_stable_version(
+            package.name(),
+            package.path_filter.as_ref(),
+            &package.changeset_directory,
+            older_tags,
+            &range,
+        )?;
+        let mut changes =
+            package
+                .versioning
+                .get_changes(&ChangeFiles::default(), &commits, split_squash_commits);
+        if package.versioning.release_notes.contributors {
+            let last_release = last_stable_tag(package.name(), older_tags);
+            contributors::add_authors(&mut changes, last_release.as_str(), username_lookup)?;
+        }
+        let package_name = package.name().clone();
+        actions.extend(package.versioning.release_notes.create_past_release(
+            version,
+            &changes,
+            &package_name,
+            tag_date(tag)?,
+            package.versioning.versions.channels(),
+        )?);
+    }
+    Ok(actions)
+}
+
+/// Create a release on each configured forge for `tag`, unless it already has one.
+fn create_missing_release(
+    release: &Release,
+    tag: &ReleaseTag,
+    state: RunType<&mut State>,
+) -> Result<(), Error> {
+    let (run_type, state) = state.take();
+    if let Some(github_config) = &state.github_config {
+        let github_state = std::mem::replace(&mut state.github, state::GitHub::New);
+        state.github = match run_type {
+            RunType::DryRun(()) => {
+                info!(
+                    "Would create a release for {tag} on GitHub if there isn't one already",
+                    tag = tag.as_str()
+                );
+                github_state
+            }
+            RunType::Real(()) => {
+                let (github_state, exists) =
+                    github_api::release_exists(tag.as_str(), github_state, github_config)?;
+                if exists {
+                    debug!("GitHub already has a release for {tag}", tag = tag.as_str());
+                    github_state
+                } else {
+                    github::release(
+                        release,
+                        RunType::Real(github_state),
+                        github_config,
+                        None,
+                        tag,
+                    )?
+                }
+            }
+        };
+    }
+    if let Some(gitea_config) = &state.gitea_config {
+        let gitea_state = std::mem::replace(&mut state.gitea, state::Gitea::New);
+        state.gitea = match run_type {
+            RunType::DryRun(()) => {
+                info!(
+                    "Would create a release for {tag} on Gitea if there isn't one already",
+                    tag = tag.as_str()
+                );
+                gitea_state
+            }
+            RunType::Real(()) => {
+                let (gitea_state, exists) =
+                    gitea_api::release_exists(tag.as_str(), gitea_state, gitea_config)?;
+                if exists {
+                    debug!("Gitea already has a release for {tag}", tag = tag.as_str());
+                    gitea_state
+                } else {
+                    gitea::release(release, RunType::Real(gitea_state), gitea_config, tag)?
+                }
+            }
+        };
+    }
+    Ok(())
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Package(#[from] super::package::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Contributors(#[from] contributors::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ReleaseNotes(#[from] knope_versioning::release_notes::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GetGitHubRelease(#[from] github_api::GetReleaseError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GitHub(#[from] github::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GetGiteaRelease(#[from] gitea_api::GetReleaseError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Gitea(#[from] gitea::Error),
+}
@@ -0,0 +1,279 @@
+use itertools::Itertools;
+use std::path::{Path, PathBuf};
+
+use knope_versioning::changes::{parse_change_file, ChangeFile, ChangeFiles, Metadata};
+use miette::{Diagnostic, LabeledSpan, NamedSource};
+use relative_path::RelativePath;
+
+use super::Package;
+use crate::fs;
+
+/// Every directory which can hold change files: the project-wide one, then those of packages.
+pub(crate) fn directories<'a>(
+    changeset_directory: &'a RelativePath,
+    packages: &'a [Package],
+) -> Vec<&'a RelativePath> {
+    let mut directories = vec![changeset_directory];
+    for package in packages {
+        let directory = package.changeset_directory.as_relative_path();
+        if !directories.contains(&directory) {
+            directories.push(directory);
+        }
+    }
+    directories
+}
+
+/// Where to write a change file for `packages`: the directory they share, if they all have the
+/// same one, otherwise the project-wide directory.
+pub(crate) fn directory_for<'a>(
+    changeset_directory: &'a RelativePath,
+    packages: &[&'a Package],
+) -> &'a RelativePath {
+    match packages
+        .iter()
+        .map(|package| package.changeset_directory.as_relative_path())
+        .dedup()
+        .exactly_one()
+    {
+        Ok(directory) => directory,
+        Err(_) => changeset_directory,
+    }
+}
+
+/// Load every change file from all [`directories`], checking that each is for configured
+/// packages and change types and that its metadata is valid.
+///
+/// All the problems in all the files are reported together, instead of skipping the bad parts.
+pub(crate) fn load(
+    changeset_directory: &RelativePath,
+    packages: &[Package],
+) -> Result<ChangeFiles, Error> {
+    let files = directories(changeset_directory, packages)
+        .into_iter()
+        .map(|directory| Ok((directory, list_directory(directory)?)))
+        .collect::<Result<Vec<_>, fs::Error>>()?;
+    parse_files(files, packages)
+}
+
+/// Every Markdown file in `directory`, in order.
+fn list_directory(directory: &RelativePath) -> Result<Vec<PathBuf>, fs::Error> {
+    let directory = directory.to_path("");
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+    let mut paths = directory
+        .read_dir()
+        .map_err(|source| fs::Error::Read {
+            path: directory.clone(),
+            source,
+        })?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Parse the change files at `paths`, grouped by the directory they're in, like [`load`].
+pub(crate) fn parse_files(
+    files: Vec<(&RelativePath, Vec<PathBuf>)>,
+    packages: &[Package],
+) -> Result<ChangeFiles, Error> {
+    let mut change_files = ChangeFiles::default();
+    let mut invalid = Vec::new();
+    for (directory, paths) in files {
+        let mut changes = Vec::new();
+        for path in paths {
+            let contents = fs::read_to_string(&path)?;
+            if let Some(change) = parse(&path, contents, packages, &mut invalid) {
+                changes.push(change);
+            }
+        }
+        change_files.add_directory(directory.to_relative_path_buf(), changes);
+    }
+
+    if invalid.is_empty() {
+        Ok(change_files)
+    } else {
+        Err(InvalidChangeFiles { files: invalid }.into())
+    }
+}
+
+/// Parse one change file, or add it to `invalid` if it has any problems.
+fn parse(
+    path: &Path,
+    contents: String,
+    packages: &[Package],
+    invalid: &mut Vec<InvalidChangeFile>,
+) -> Option<(changesets::Change, Metadata)> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file = parse_change_file(&file_name, &contents);
+    let problems = check(&file, packages);
+    if problems.is_empty() {
+        return file.into_change().ok();
+    }
+    invalid.push(InvalidChangeFile {
+        path: path.display().to_string(),
+        help: contents
+            .trim()
+            .is_empty()
+            .then(|| "This file is empty".to_string()),
+        source_code: NamedSource::new(path.display().to_string(), contents),
+        problems,
+    });
+    None
+}
+
+/// Label every problem with a change file: those with its front matter, and any packages or
+/// change types which aren't configured.
+fn check(file: &ChangeFile, packages: &[Package]) -> Vec<LabeledSpan> {
+    let mut problems = file
+        .problems
+        .iter()
+        .map(|problem| LabeledSpan::at(problem.span.clone(), problem.error.to_string()))
+        .collect::<Vec<_>>();
+    for listed in &file.packages {
+        let Some(package) = packages
+            .iter()
+            .find(|package| package.name().as_ref() == listed.name)
+        else {
+            problems.push(LabeledSpan::at(
+                listed.name_span.clone(),
+                format!("Unknown package `{}`", listed.name),
+            ));
+            continue;
+        };
+        if !package.change_file_types().contains(&listed.change_type) {
+            problems.push(LabeledSpan::at(
+                listed.change_type_span.clone(),
+                format!(
+                    "No changelog section of `{}` handles `{}`",
+                    listed.name, listed.change_type
+                ),
+            ));
+        }
+    }
+    problems.sort_by_key(LabeledSpan::offset);
+    problems
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Fs(#[from] fs::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Invalid(#[from] InvalidChangeFiles),
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+#[error("Found {count} invalid change files", count = self.files.len())]
+#[diagnostic(
+    code(change_files::invalid),
+    help("Change files must start with front matter between `---` lines, listing configured packages and their change types like `my-package: minor`."),
+    url("https://knope.tech/reference/concepts/change-file/")
+)]
+pub(crate) struct InvalidChangeFiles {
+    #[related]
+    files: Vec<InvalidChangeFile>,
+}
+
+/// A change file with problems in its front matter, each labeled.
+#[derive(Debug, Diagnostic, thiserror::Error)]
+#[error("Change file {path} is not valid")]
+#[diagnostic(code(change_files::invalid_file))]
+pub(crate) struct InvalidChangeFile {
+    path: String,
+    #[help]
+    help: Option<String>,
+    #[source_code]
+    source_code: NamedSource<String>,
+    #[label(collection)]
+    problems: Vec<LabeledSpan>,
+}
+
+#[cfg(test)]
+mod test_check {
+    use super::*;
+
+    fn labels(contents: &str) -> Vec<String> {
+        check(
+            &parse_change_file("a_change.md", contents),
+            &[Package::default()],
+        )
+        .into_iter()
+        .filter_map(|span| span.label().map(String::from))
+        .collect()
+    }
+
+    #[test]
+    fn valid() {
+        assert!(labels("---\ndefault: minor\n---\n\n# Summary\n").is_empty());
+        assert!(
+            labels("---\ndefault: minor\nissues: [1, ABC-2]\npr: 3\nauthors: [alice]\n---\n")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        assert_eq!(
+            labels("---\nother: minor\ndefault: poem\ndefault: major\nnope\n---\n# Summary\n"),
+            vec![
+                "Unknown package `other`",
+                "No changelog section of `default` handles `poem`",
+                "`default` is listed more than once",
+                "Expected a line like `package: change type`",
+            ]
+        );
+    }
+
+    #[test]
+    fn broken_front_matter() {
+        assert_eq!(
+            labels("# Summary\n"),
+            vec!["Expected front matter starting with `---`"]
+        );
+        assert_eq!(
+            labels("---\ndefault: minor\n# Summary\n"),
+            vec!["This front matter is never closed with `---`"]
+        );
+        assert_eq!(
+            labels("---\n---\n# Summary\n"),
+            vec!["No packages are listed"]
+        );
+        assert_eq!(
+            labels("---\npr: 3\n---\n# Summary\n"),
+            vec!["No packages are listed"]
+        );
+    }
+
+    #[test]
+    fn directory_for_packages() {
+        let project_wide = RelativePath::new(".changes");
+        let mut first = Package::default();
+        first.changeset_directory = "first/.changes".into();
+        let second = Package::default();
+        assert_eq!(
+            directory_for(project_wide, &[&first]),
+            RelativePath::new("first/.changes")
+        );
+        assert_eq!(
+            directory_for(project_wide, &[&first, &second]),
+            project_wide
+        );
+        assert_eq!(
+            directories(project_wide, &[first.clone(), second, first]),
+            vec![
+                project_wide,
+                RelativePath::new("first/.changes"),
+                RelativePath::new(".changeset")
+            ]
+        );
+    }
+}
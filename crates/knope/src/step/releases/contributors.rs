@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use knope_versioning::changes::{conventional_commit::CommitSource, Change, ChangeSource};
+use miette::Diagnostic;
+
+use crate::{
+    config,
+    integrations::{git, gitea, github},
+    state,
+    state::RunType,
+};
+
+/// Where to look up usernames for the "Contributors" section of release notes.
+pub(crate) enum UsernameLookup<'a> {
+    GitHub {
+        config: &'a config::GitHub,
+        state: &'a mut state::GitHub,
+    },
+    Gitea {
+        config: &'a config::Gitea,
+        state: &'a mut state::Gitea,
+    },
+    /// Only use usernames from GitHub `noreply` emails
+    None,
+}
+
+impl<'a> UsernameLookup<'a> {
+    /// Look usernames up with whichever forge is configured.
+    ///
+    /// Dry runs never talk to forges, so only usernames from GitHub `noreply` emails are known.
+    pub(crate) fn new(
+        run_type: RunType<()>,
+        github: (Option<&'a config::GitHub>, &'a mut state::GitHub),
+        gitea: (Option<&'a config::Gitea>, &'a mut state::Gitea),
+    ) -> Self {
+        match (run_type, github, gitea) {
+            (RunType::DryRun(()), _, _) | (RunType::Real(()), (None, _), (None, _)) => Self::None,
+            (RunType::Real(()), (Some(config), state), _) => Self::GitHub { config, state },
+            (RunType::Real(()), (None, _), (Some(config), state)) => Self::Gitea { config, state },
+        }
+    }
+
+    /// The forge username of whoever authored the commit `sha`, if the forge knows them.
+    fn login(&mut self, sha: &str) -> Result<Option<String>, Error> {
+        match self {
+            Self::GitHub { config, state } => {
+                let current = std::mem::replace(*state, state::GitHub::New);
+                let (new_state, login) = github::get_commit_author(sha, current, config)?;
+                **state = new_state;
+                Ok(login)
+            }
+            Self::Gitea { config, state } => {
+                let current = std::mem::replace(*state, state::Gitea::New);
+                let (new_state, login) = gitea::get_commit_author(sha, current, config)?;
+                **state = new_state;
+                Ok(login)
+            }
+            Self::None => Ok(None),
+        }
+    }
+}
+
+/// Find who wrote each change, for the "Contributors" section of release notes.
+///
+/// Change files are credited to the author of the commit which added them since `last_release`
+/// (a tag). Authors without a known username have it looked up (once per commit) using `lookup`.
+pub(crate) fn add_authors(
+    changes: &mut [Change],
+    last_release: &str,
+    lookup: &mut UsernameLookup,
+) -> Result<(), Error> {
+    let change_files = changes
+        .iter()
+        .filter_map(|change| match &change.original_source {
+            ChangeSource::ChangeFile { path, .. } => Some(path.clone()),
+            ChangeSource::ConventionalCommit(_) => None,
+        })
+        .collect_vec();
+    let added_by = if change_files.is_empty() {
+        HashMap::new()
+    } else {
+        git::find_adding_commits(&change_files, last_release)?
+    };
+
+    let mut logins: HashMap<String, Option<String>> = HashMap::new();
+    for change in changes {
+        let (sha, author) = match &mut change.original_source {
+            ChangeSource::ConventionalCommit(CommitSource {
+                hash: Some(hash),
+                author: Some(author),
+                ..
+            }) => (hash.clone(), author),
+            // Authors listed in the change file replace whoever added it
+            ChangeSource::ChangeFile { authors, .. } if !authors.is_empty() => continue,
+            ChangeSource::ChangeFile { path, author, .. } => {
+                let Some(added) = added_by.get(path) else {
+                    continue;
+                };
+                (added.id.clone(), author.insert(added.author.clone()))
+            }
+            ChangeSource::ConventionalCommit(_) => continue,
+        };
+        if author.username().is_some() {
+            continue;
+        }
+        let login = if let Some(login) = logins.get(&sha) {
+            login.clone()
+        } else {
+            let login = lookup.login(&sha)?;
+            logins.insert(sha, login.clone());
+            login
+        };
+        author.login = login;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GitHub(#[from] github::GetCommitAuthorError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Gitea(#[from] gitea::GetCommitAuthorError),
+}
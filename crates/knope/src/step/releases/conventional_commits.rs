@@ -1,18 +1,353 @@
-use knope_versioning::{package, semver::PackageVersions, ReleaseTag};
+use git_conventional::Commit as ParsedCommit;
+use glob::Pattern;
+use knope_config::ScopesAndPaths;
+use knope_versioning::{
+    changes::conventional_commit::Commit,
+    package,
+    semver::{Channels, PackageVersions},
+    ReleaseTag,
+};
+use relative_path::{RelativePath, RelativePathBuf};
 use tracing::debug;
 
-use crate::integrations::git::{self, get_commit_messages_after_tag};
+use crate::integrations::git::{self, get_commits_after_tag, CommitMessage, CommitRange};
+
+/// Decides which commits belong to a package based on the files they touch.
+#[derive(Clone, Debug)]
+pub(crate) struct PathFilter {
+    pub(crate) paths: Vec<Pattern>,
+    /// Only set when [`ScopesAndPaths::Any`], otherwise scopes are checked with the rest of the
+    /// changes.
+    pub(crate) scopes: Option<Vec<String>>,
+    pub(crate) mode: ScopesAndPaths,
+}
+
+impl PathFilter {
+    fn includes(&self, commit: &CommitMessage) -> bool {
+        match self.mode {
+            ScopesAndPaths::All => commit.touches_paths,
+            ScopesAndPaths::Any => commit.touches_paths || self.has_scope(&commit.message),
+        }
+    }
+
+    fn has_scope(&self, message: &str) -> bool {
+        let Some(scopes) = &self.scopes else {
+            return false;
+        };
+        ParsedCommit::parse(message.trim())
+            .ok()
+            .and_then(|commit| commit.scope())
+            .is_some_and(|scope| {
+                scopes
+                    .iter()
+                    .any(|it| it.eq_ignore_ascii_case(scope.as_str()))
+            })
+    }
+}
+
+/// Records the commits which `ConvertCommits` turned into change files, one hash per line.
+const CONVERTED_COMMITS_FILE: &str = ".converted_commits";
+
+/// Where the commits converted for packages with change files in `changeset_directory` are recorded.
+pub(crate) fn converted_commits_path(changeset_directory: &RelativePath) -> RelativePathBuf {
+    changeset_directory.join(CONVERTED_COMMITS_FILE)
+}
+
+/// The hashes of commits which were already converted into change files in `changeset_directory`.
+pub(crate) fn converted_commits(changeset_directory: &RelativePath) -> Vec<String> {
+    std::fs::read_to_string(converted_commits_path(changeset_directory).to_path(""))
+        .map(|contents| {
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The tag of the last stable release of a package, which may not exist yet.
+pub(crate) fn last_stable_tag(package_name: &package::Name, all_tags: &[String]) -> ReleaseTag {
+    // Channels only affect pre-releases, so they don't matter for the stable version
+    let target_version =
+        PackageVersions::from_tags(package_name.as_custom(), all_tags, Channels::default())
+            .stable();
+    ReleaseTag::new(&target_version.into(), package_name)
+}
 
 pub(crate) fn get_conventional_commits_after_last_stable_version(
     package_name: &package::Name,
+    path_filter: Option<&PathFilter>,
+    changeset_directory: &RelativePath,
     all_tags: &[String],
-) -> Result<Vec<String>, git::Error> {
+    range: &CommitRange,
+) -> Result<Vec<Commit>, git::Error> {
     debug!(
         "Getting conventional commits since last release of package {}",
         package_name.as_custom().unwrap_or_default()
     );
-    let target_version = PackageVersions::from_tags(package_name.as_custom(), all_tags).stable();
-    let tag = ReleaseTag::new(&target_version.into(), package_name);
+    let tag = last_stable_tag(package_name, all_tags);
+
+    let paths = path_filter.map_or(&[][..], |filter| filter.paths.as_slice());
+    let commits = get_commits_after_tag(tag.as_str(), range, paths)?;
+    Ok(conventional_commits_for(
+        path_filter,
+        changeset_directory,
+        commits,
+    ))
+}
+
+/// The commits which belong to a package, leaving out any which were reverted or already
+/// converted to change files in `changeset_directory`.
+///
+/// Each commit's [`CommitMessage::touches_paths`] must already be set for the paths of
+/// `path_filter`.
+pub(crate) fn conventional_commits_for(
+    path_filter: Option<&PathFilter>,
+    changeset_directory: &RelativePath,
+    commits: Vec<CommitMessage>,
+) -> Vec<Commit> {
+    let converted = converted_commits(changeset_directory);
+    drop_reverted(commits)
+        .into_iter()
+        .filter(|commit| {
+            let is_converted = converted.contains(&commit.id);
+            if is_converted {
+                debug!(
+                    "Skipping commit {} which was converted to a change file",
+                    commit.id
+                );
+            }
+            !is_converted
+        })
+        .filter(|commit| {
+            let Some(filter) = path_filter else {
+                return true;
+            };
+            let included = filter.includes(commit);
+            if !included {
+                debug!(
+                    "Skipping commit which doesn't touch {paths:?}: {message}",
+                    paths = filter.paths,
+                    message = commit.message.lines().next().unwrap_or_default()
+                );
+            }
+            included
+        })
+        .map(|commit| Commit {
+            message: commit.message,
+            hash: Some(commit.id),
+            author: Some(commit.author),
+        })
+        .collect()
+}
+
+/// The shortest abbreviated commit ID a revert can refer to, like Git's default abbreviation.
+const MIN_REVERT_ID_LEN: usize = 7;
+
+/// What a revert commit points at.
+enum RevertTarget<'a> {
+    /// From git's `This reverts commit <sha>.` line (or `This reverts commit <sha>, reversing
+    /// changes made to <sha>.` for merges), may be abbreviated to [`MIN_REVERT_ID_LEN`]
+    Id(&'a str),
+    /// The summary line of the reverted commit
+    Summary(&'a str),
+}
+
+impl<'a> RevertTarget<'a> {
+    fn parse(message: &'a str) -> Option<Self> {
+        let message = message.trim();
+        if let Some(id) = message.lines().find_map(|line| {
+            let rest = line.trim().strip_prefix("This reverts commit ")?;
+            let len = rest
+                .find(|c: char| !c.is_ascii_hexdigit())
+                .unwrap_or(rest.len());
+            rest.get(..len).filter(|id| id.len() >= MIN_REVERT_ID_LEN)
+        }) {
+            return Some(Self::Id(id));
+        }
+        let summary = message.lines().next().unwrap_or_default().trim();
+        if let Some(quoted) = summary
+            .strip_prefix("Revert \"")
+            .and_then(|rest| rest.strip_suffix('"'))
+        {
+            return Some(Self::Summary(quoted));
+        }
+        let (commit_type, description) = summary.split_once(':')?;
+        let commit_type = commit_type
+            .split_once('(')
+            .map_or(commit_type, |(commit_type, _)| commit_type);
+        commit_type
+            .trim_end_matches('!')
+            .eq_ignore_ascii_case("revert")
+            .then(|| Self::Summary(description.trim()))
+    }
+
+    fn matches(&self, commit: &CommitMessage) -> bool {
+        match self {
+            Self::Id(id) => commit.id.starts_with(id),
+            Self::Summary(summary) => {
+                commit.message.lines().next().unwrap_or_default().trim() == *summary
+            }
+        }
+    }
+}
+
+/// Remove any reverted commits along with the commits that reverted them.
+///
+/// A revert can be a conventional commit like `revert: feat: Add X` or Git's default
+/// `Revert "feat: Add X"` message (which contains `This reverts commit <sha>.`, where `<sha>` is
+/// at least [`MIN_REVERT_ID_LEN`] hex characters).
+/// Reverts whose target isn't in `commits` are left alone.
+fn drop_reverted(commits: Vec<CommitMessage>) -> Vec<CommitMessage> {
+    let mut kept: Vec<CommitMessage> = Vec::with_capacity(commits.len());
+    // Pairs of (revert, original) which were dropped, in case the revert itself is reverted
+    let mut cancelled: Vec<(CommitMessage, CommitMessage)> = Vec::new();
+    for commit in commits {
+        let Some(target) = RevertTarget::parse(&commit.message) else {
+            kept.push(commit);
+            continue;
+        };
+        if let Some(index) = kept.iter().rposition(|it| target.matches(it)) {
+            let original = kept.remove(index);
+            debug!(
+                "Skipping commit reverted by {revert}: {original}",
+                revert = commit.message.lines().next().unwrap_or_default(),
+                original = original.message.lines().next().unwrap_or_default()
+            );
+            cancelled.push((commit, original));
+        } else if let Some(index) = cancelled
+            .iter()
+            .rposition(|(revert, _)| target.matches(revert))
+        {
+            let (_, original) = cancelled.remove(index);
+            debug!(
+                "Restoring commit whose revert was reverted: {original}",
+                original = original.message.lines().next().unwrap_or_default()
+            );
+            kept.push(original);
+        } else {
+            kept.push(commit);
+        }
+    }
+    kept
+}
+
+#[cfg(test)]
+mod test_drop_reverted {
+    use knope_versioning::changes::conventional_commit::Author;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn commit(id: &str, message: &str) -> CommitMessage {
+        CommitMessage {
+            id: id.to_string(),
+            message: message.to_string(),
+            author: Author::default(),
+            touches_paths: false,
+            is_merge: false,
+        }
+    }
+
+    fn messages(commits: Vec<CommitMessage>) -> Vec<String> {
+        commits.into_iter().map(|commit| commit.message).collect()
+    }
+
+    #[test]
+    fn conventional_revert() {
+        let commits = vec![
+            commit("aaa", "feat: Add X"),
+            commit("bbb", "fix: Fix Y"),
+            commit("ccc", "revert: feat: Add X"),
+        ];
+        assert_eq!(messages(drop_reverted(commits)), vec!["fix: Fix Y"]);
+    }
+
+    #[test]
+    fn git_revert_by_id() {
+        let commits = vec![
+            commit("abc1234def", "feat: Add X"),
+            commit("bbb", "feat: Add X"),
+            commit(
+                "ccc",
+                "Revert \"something else\"\n\nThis reverts commit abc1234def.",
+            ),
+        ];
+        assert_eq!(messages(drop_reverted(commits)), vec!["feat: Add X"]);
+        assert_eq!(
+            drop_reverted(vec![
+                commit("abc1234def", "feat: Add X"),
+                commit(
+                    "bbb",
+                    "Revert \"feat: Add X\"\n\nThis reverts commit abc1234."
+                ),
+            ]),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn git_revert_by_summary() {
+        let commits = vec![
+            commit("aaa", "feat(scope): Add X"),
+            commit("bbb", "Revert \"feat(scope): Add X\""),
+        ];
+        assert_eq!(drop_reverted(commits), Vec::new());
+    }
+
+    #[test]
+    fn revert_outside_of_range_is_kept() {
+        let commits = vec![commit("aaa", "revert: feat: Old feature")];
+        assert_eq!(
+            messages(drop_reverted(commits)),
+            vec!["revert: feat: Old feature"]
+        );
+    }
+
+    #[test]
+    fn revert_of_revert_restores_original() {
+        let commits = vec![
+            commit("aaaaaaa1", "feat: Add X"),
+            commit(
+                "bbbbbbb2",
+                "Revert \"feat: Add X\"\n\nThis reverts commit aaaaaaa1.",
+            ),
+            commit(
+                "ccccccc3",
+                "Revert \"Revert \"feat: Add X\"\"\n\nThis reverts commit bbbbbbb2.",
+            ),
+        ];
+        assert_eq!(messages(drop_reverted(commits)), vec!["feat: Add X"]);
+    }
+
+    #[test]
+    fn short_id_is_not_a_target() {
+        let commits = vec![
+            commit("abc1234def", "feat: Add X"),
+            commit("abd5678def", "fix: Fix Y"),
+            commit("ccc", "chore: Tidy up\n\nThis reverts commit a."),
+        ];
+        assert_eq!(
+            messages(drop_reverted(commits)),
+            vec![
+                "feat: Add X",
+                "fix: Fix Y",
+                "chore: Tidy up\n\nThis reverts commit a."
+            ]
+        );
+    }
 
-    get_commit_messages_after_tag(tag.as_str()).map_err(git::Error::from)
+    #[test]
+    fn merge_revert() {
+        let commits = vec![
+            commit("abc1234def", "feat: Merged feature"),
+            commit(
+                "ccc",
+                "Revert \"Merge branch 'feature'\"\n\nThis reverts commit abc1234def, reversing\nchanges made to 1234567abc.",
+            ),
+        ];
+        assert_eq!(drop_reverted(commits), Vec::new());
+    }
 }
@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+
+use changesets::{UniqueId, Versioning};
+use indexmap::IndexMap;
+use itertools::Itertools;
+use knope_versioning::changes::{conventional_commit::CommitSource, ChangeFiles, ChangeSource};
+use miette::Diagnostic;
+use relative_path::RelativePath;
+use tracing::{debug, info};
+
+use super::{
+    change_files,
+    conventional_commits::{
+        converted_commits, converted_commits_path,
+        get_conventional_commits_after_last_stable_version,
+    },
+    Package,
+};
+use crate::{
+    fs,
+    fs::WriteType,
+    integrations::git::{self, CommitRange},
+    state::{RunType, State},
+};
+
+/// The change file that one or more commits become.
+#[derive(Default)]
+struct Conversion<'a> {
+    versioning: Vec<(String, changesets::ChangeType)>,
+    packages: Vec<&'a Package>,
+    commits: Vec<String>,
+}
+
+/// Turn the changes that pending conventional commits would make into change files, and record
+/// those commits so that later steps don't count them again.
+///
+/// A commit is left alone if any of its changes can't be expressed as a change file (like a
+/// `Changelog-Note` footer without a matching change type, or `Changelog: skip`).
+pub(crate) fn convert_commits(state: RunType<State>) -> Result<RunType<State>, Error> {
+    let (run_type, state) = state.take();
+    if state.packages.is_empty() {
+        return Err(super::package::Error::NoDefinedPackages.into());
+    }
+
+    let directories = change_files::directories(&state.changeset_directory, &state.packages);
+    let mut conversions: IndexMap<String, Conversion> = IndexMap::new();
+    let mut blocked = HashSet::new();
+    for package in &state.packages {
+        collect_conversions(
+            package,
+            &state.all_git_tags,
+            &directories,
+            &mut conversions,
+            &mut blocked,
+        )?;
+    }
+    // Blocking one commit can leave another commit with the same description behind, so repeat
+    // until nothing changes.
+    loop {
+        let before = conversions.len();
+        conversions.retain(|_, conversion| {
+            let keep = !conversion
+                .commits
+                .iter()
+                .any(|commit| blocked.contains(commit));
+            if !keep {
+                blocked.extend(conversion.commits.iter().cloned());
+            }
+            keep
+        });
+        if conversions.len() == before {
+            break;
+        }
+    }
+
+    if conversions.is_empty() {
+        info!("No commits to convert into change files");
+        return Ok(run_type.of(state));
+    }
+
+    let conversions = conversions
+        .into_iter()
+        .map(|(description, conversion)| {
+            let directory =
+                change_files::directory_for(&state.changeset_directory, &conversion.packages);
+            (directory, description, conversion)
+        })
+        .collect_vec();
+    // Each package skips the commits recorded in its own change file directory
+    let mut recorded: IndexMap<&RelativePath, Vec<String>> = IndexMap::new();
+    for (_, _, conversion) in &conversions {
+        for package in &conversion.packages {
+            let directory = package.changeset_directory.as_relative_path();
+            let recorded = recorded
+                .entry(directory)
+                .or_insert_with(|| converted_commits(directory));
+            for commit in &conversion.commits {
+                if !recorded.contains(commit) {
+                    recorded.push(commit.clone());
+                }
+            }
+        }
+    }
+
+    for directory in conversions
+        .iter()
+        .map(|(directory, _, _)| *directory)
+        .chain(recorded.keys().copied())
+        .unique()
+    {
+        let path = directory.to_path("");
+        if !path.exists() {
+            fs::create_dir(run_type.of(&path))?;
+        }
+    }
+    for (directory, description, conversion) in conversions {
+        write_change_file(
+            run_type,
+            directory,
+            &description,
+            conversion.versioning,
+            &conversion.commits,
+        )?;
+    }
+    for (directory, commits) in recorded {
+        let contents = commits.iter().map(|commit| format!("{commit}\n")).join("");
+        let write_type = match run_type {
+            RunType::DryRun(()) => WriteType::DryRun(contents),
+            RunType::Real(()) => WriteType::Real(contents),
+        };
+        fs::write(write_type, &converted_commits_path(directory).to_path(""))?;
+    }
+    Ok(run_type.of(state))
+}
+
+fn write_change_file(
+    run_type: RunType<()>,
+    directory: &RelativePath,
+    description: &str,
+    versioning: Vec<(String, changesets::ChangeType)>,
+    commits: &[String],
+) -> Result<(), Error> {
+    let change = changesets::Change {
+        unique_id: UniqueId::from(description),
+        versioning: versioning.into_iter().collect::<Versioning>(),
+        summary: format!("# {description}"),
+    };
+    let changeset_path = directory.to_path("");
+    let path = changeset_path.join(change.unique_id.to_file_name());
+    match run_type {
+        RunType::DryRun(()) => info!(
+            "Would create {} from {}",
+            path.display(),
+            commits.iter().map(|commit| short(commit)).join(", ")
+        ),
+        RunType::Real(()) => {
+            change
+                .write_to_directory(&changeset_path)
+                .map_err(|source| fs::Error::Write { path, source })?;
+        }
+    }
+    Ok(())
+}
+
+/// Add the changes from pending commits of `package` to `conversions`, or the commit to `blocked`
+/// if one of its changes can't become a change file.
+fn collect_conversions<'a>(
+    package: &'a Package,
+    all_tags: &[String],
+    directories: &[&RelativePath],
+    conversions: &mut IndexMap<String, Conversion<'a>>,
+    blocked: &mut HashSet<String>,
+) -> Result<(), Error> {
+    let commits = get_conventional_commits_after_last_stable_version(
+        package.name(),
+        package.path_filter.as_ref(),
+        &package.changeset_directory,
+        all_tags,
+        &CommitRange::default(),
+    )?;
+    let change_types = package.change_file_types();
+    for change in package
+        .versioning
+        .get_changes(&ChangeFiles::default(), &commits, false)
+    {
+        let ChangeSource::ConventionalCommit(CommitSource {
+            hash: Some(hash), ..
+        }) = &change.original_source
+        else {
+            continue;
+        };
+        let change_type = change
+            .change_type
+            .to_changeset_type()
+            .filter(|change_type| change_types.contains(change_type));
+        let (Some(change_type), false) = (change_type, change.skip_release_notes) else {
+            debug!(
+                "Not converting commit {} because its change \"{}\" can't be a change file",
+                short(hash),
+                change.description
+            );
+            blocked.insert(hash.clone());
+            continue;
+        };
+        let description = change.description.to_string();
+        let file_name = UniqueId::from(&description).to_file_name();
+        if !conversions.contains_key(&description)
+            && directories
+                .iter()
+                .any(|directory| directory.join(&file_name).to_path("").exists())
+        {
+            debug!(
+                "Not converting commit {} because a change file for \"{description}\" already exists",
+                short(hash)
+            );
+            blocked.insert(hash.clone());
+            continue;
+        }
+        let conversion = conversions.entry(description).or_default();
+        let package_name = package.name().to_string();
+        if !conversion
+            .versioning
+            .iter()
+            .any(|(name, _)| *name == package_name)
+        {
+            conversion.versioning.push((package_name, change_type));
+            conversion.packages.push(package);
+        }
+        if !conversion.commits.contains(hash) {
+            conversion.commits.push(hash.clone());
+        }
+    }
+    Ok(())
+}
+
+fn short(hash: &str) -> &str {
+    hash.get(..7).unwrap_or(hash)
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Package(#[from] super::package::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Fs(#[from] fs::Error),
+}
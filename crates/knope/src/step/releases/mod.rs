@@ -1,32 +1,36 @@
-use std::path::PathBuf;
+use std::cmp::Ordering;
 
-use changesets::ChangeSet;
 use itertools::Itertools;
 use knope_versioning::{
-    changes::CHANGESET_DIR,
-    package::Bump,
-    release_notes::Release,
-    semver::{PackageVersions, Rule},
-    Action, ReleaseTag, VersionedFile,
+    package::Bump, release_notes::Release, semver::PackageVersions, Action, ReleaseTag,
+    VersionedFile,
 };
 use miette::Diagnostic;
+use relative_path::RelativePath;
 use tracing::debug;
 
+use self::contributors::UsernameLookup;
 pub(crate) use self::{package::Package, semver::bump_version_and_update_state};
 use crate::{
     fs,
     integrations::{git, git::create_tag},
     state::State,
-    step::{releases::package::execute_prepare_actions, PrepareRelease},
+    step::{releases::package::execute_prepare_actions, BumpVersion, PrepareRelease},
     RunType,
 };
 
+pub(crate) mod backfill;
+pub(crate) mod change_files;
 pub(crate) mod changelog;
+pub(crate) mod contributors;
 pub(crate) mod conventional_commits;
+pub(crate) mod convert_commits;
 pub(crate) mod gitea;
 pub(crate) mod github;
 pub(crate) mod package;
 pub(crate) mod semver;
+pub(crate) mod snapshot;
+pub(crate) mod unreleased;
 
 pub(crate) fn prepare_release(
     state: RunType<State>,
@@ -37,38 +41,66 @@ pub(crate) fn prepare_release(
         return Err(package::Error::NoDefinedPackages.into());
     }
 
-    let changeset_path = PathBuf::from(CHANGESET_DIR);
-    let changeset = if changeset_path.exists() {
-        ChangeSet::from_directory(&changeset_path)?.into()
-    } else {
-        Vec::new()
-    };
+    let change_files = change_files::load(&state.changeset_directory, &state.packages)?;
 
+    let mut username_lookup = UsernameLookup::new(
+        run_type,
+        (state.github_config.as_ref(), &mut state.github),
+        (state.gitea_config.as_ref(), &mut state.gitea),
+    );
     for package in &mut state.packages {
         let (all_versioned_files, actions) = package.prepare_release(
             prepare_release,
             &state.all_git_tags,
             state.all_versioned_files,
-            &changeset,
+            &change_files,
+            &mut username_lookup,
         )?;
         state.all_versioned_files = all_versioned_files;
         state.pending_actions.extend(actions);
     }
 
-    let actions = state
+    let mut actions = state
         .all_versioned_files
         .drain(..)
         .filter_map(VersionedFile::write)
         .flatten()
         .chain(state.pending_actions)
-        .unique();
+        .unique()
+        .collect_vec();
+    // Once change files are consumed, so are the commits that were converted into them
+    let consumed_directories = actions
+        .iter()
+        .filter_map(|action| match action {
+            Action::RemoveFile { path } => path.parent().map(RelativePath::normalize),
+            _ => None,
+        })
+        .unique()
+        .collect_vec();
+    for directory in change_files::directories(&state.changeset_directory, &state.packages) {
+        let converted_commits = conventional_commits::converted_commits_path(directory);
+        if consumed_directories.contains(&directory.normalize())
+            && converted_commits.to_path("").exists()
+        {
+            actions.push(Action::RemoveFile {
+                path: converted_commits,
+            });
+        }
+    }
+    // Snapshots don't leave any release actions behind, they only change files
+    let nothing_to_snapshot = prepare_release.snapshot.is_some() && actions.is_empty();
 
-    state.pending_actions = execute_prepare_actions(run_type.of(actions), true)?;
+    state.pending_actions = execute_prepare_actions(run_type.of(actions.into_iter()), true)?;
 
     match run_type {
         RunType::DryRun(()) => Ok(RunType::DryRun(state)),
         RunType::Real(()) => {
-            if !prepare_release.allow_empty && state.pending_actions.is_empty() {
+            let nothing_to_release = if prepare_release.snapshot.is_some() {
+                nothing_to_snapshot
+            } else {
+                state.pending_actions.is_empty()
+            };
+            if !prepare_release.allow_empty && nothing_to_release {
                 Err(Error::NoRelease)
             } else {
                 Ok(RunType::Real(state))
@@ -77,8 +109,27 @@ pub(crate) fn prepare_release(
     }
 }
 
-pub(crate) fn bump_version(state: RunType<State>, rule: &Rule) -> Result<RunType<State>, Error> {
-    bump_version_and_update_state(state, rule).map_err(Error::from)
+pub(crate) fn bump_version(
+    state: RunType<State>,
+    bump_version: &BumpVersion,
+) -> Result<RunType<State>, Error> {
+    bump_version_and_update_state(state, bump_version).map_err(Error::from)
+}
+
+pub(crate) fn backfill(
+    state: RunType<State>,
+    create_releases: bool,
+    split_squash_commits: bool,
+) -> Result<RunType<State>, Error> {
+    backfill::backfill(state, create_releases, split_squash_commits).map_err(Error::from)
+}
+
+pub(crate) fn convert_commits(state: RunType<State>) -> Result<RunType<State>, Error> {
+    convert_commits::convert_commits(state).map_err(Error::from)
+}
+
+pub(crate) fn update_unreleased(state: RunType<State>) -> Result<RunType<State>, Error> {
+    unreleased::update_unreleased(state).map_err(Error::from)
 }
 
 #[derive(Debug, Diagnostic, thiserror::Error)]
@@ -106,16 +157,20 @@ pub(crate) enum Error {
     #[diagnostic(transparent)]
     Gitea(#[from] gitea::Error),
     #[error(transparent)]
-    #[diagnostic(
-        code(changesets::could_not_read_changeset),
-        help(
-            "This could be a file-system issue or a problem with the formatting of a change file."
-        )
-    )]
-    CouldNotReadChangeSet(#[from] changesets::LoadingError),
+    #[diagnostic(transparent)]
+    ChangeFiles(#[from] change_files::Error),
     #[error(transparent)]
     #[diagnostic(transparent)]
     Fs(#[from] fs::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Backfill(#[from] backfill::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ConvertCommits(#[from] convert_commits::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Unreleased(#[from] unreleased::Error),
 }
 
 /// Create a release for the package.
@@ -201,8 +256,19 @@ pub(crate) fn release(state: RunType<State>) -> Result<RunType<State>, Error> {
 fn find_prepared_release(package: &mut Package, all_tags: &[String]) -> Option<Release> {
     let current_version = package.versioning.versions.clone().into_latest();
     debug!("Searching for last package tag to determine if there's a release to release");
-    let last_tag = PackageVersions::from_tags(package.name().as_custom(), all_tags).into_latest();
-    if last_tag == current_version {
+    let last_tag = PackageVersions::from_tags(
+        package.name().as_custom(),
+        all_tags,
+        package.versioning.versions.channels().clone(),
+    )
+    .into_latest();
+    if package
+        .versioning
+        .versions
+        .channels()
+        .cmp_versions(&current_version, &last_tag)
+        != Ordering::Greater
+    {
         return None;
     }
     package
@@ -1,22 +1,29 @@
 use std::{fmt, fmt::Display};
 
+use glob::Pattern;
 use itertools::Itertools;
-use knope_config::{changelog_section::convert_to_versioning, Assets};
+use knope_config::{changelog_section::convert_to_versioning, Assets, ScopesAndPaths};
 use knope_versioning::{
+    changes::{conventional_commit::CommitFilter, ChangeFiles, ChangeType},
     package::{BumpError, ChangeConfig, Name},
-    release_notes::{ReleaseNotes, TimeError},
-    semver::Version,
-    Action, GoVersioning, PackageNewError, VersionedFile, VersionedFileError,
+    release_notes::{Forge, Jira, ReleaseNotes, Template, TemplateError, Templates},
+    semver::{PackageVersions, Version},
+    Action, GoVersioning, PackageNewError, ReleaseTag, VersionedFile, VersionedFileError,
 };
 use miette::Diagnostic;
-use relative_path::RelativePathBuf;
+use regex::Regex;
+use relative_path::{RelativePath, RelativePathBuf};
 use tracing::{debug, info};
 
-use super::{conventional_commits, semver};
+use super::{
+    contributors::{self, UsernameLookup},
+    conventional_commits::{self, PathFilter},
+    semver, snapshot,
+};
 use crate::{
     config, fs,
     fs::{read_to_string, WriteType},
-    integrations::git::{self, add_files},
+    integrations::git::{self, add_files, CommitRange},
     state::RunType,
     step::{releases::changelog::load_changelog, PrepareRelease},
 };
@@ -28,12 +35,19 @@ pub(crate) struct Package {
     pub(crate) override_version: Option<Version>,
     pub(crate) assets: Option<Assets>,
     pub(crate) go_versioning: GoVersioning,
+    /// Only consider commits that touch these paths
+    pub(crate) path_filter: Option<PathFilter>,
+    /// Where change files for this package are
+    pub(crate) changeset_directory: RelativePathBuf,
 }
 
 impl Package {
     pub(crate) fn load(
         packages: Vec<config::Package>,
         git_tags: &[String],
+        forge: Option<&Forge>,
+        jira: Option<&Jira>,
+        changeset_directory: &RelativePath,
     ) -> Result<(Vec<Self>, Vec<VersionedFile>), Error> {
         let versioned_files: Vec<VersionedFile> = packages
             .iter()
@@ -45,7 +59,16 @@ impl Package {
             .try_collect()?;
         let packages = packages
             .into_iter()
-            .map(|package| Package::validate(package, git_tags, &versioned_files))
+            .map(|package| {
+                Package::validate(
+                    package,
+                    git_tags,
+                    &versioned_files,
+                    forge,
+                    jira,
+                    changeset_directory,
+                )
+            })
             .collect::<Result<Vec<_>, _>>()?;
         Ok((packages, versioned_files))
     }
@@ -54,16 +77,62 @@ impl Package {
         &self.versioning.name
     }
 
+    /// The paths that commits must touch to belong to this package, if any.
+    pub(crate) fn paths(&self) -> &[Pattern] {
+        self.path_filter
+            .as_ref()
+            .map_or(&[][..], |filter| filter.paths.as_slice())
+    }
+
+    /// The change types that change files can use for this package, like `minor` or `poem`.
+    pub(crate) fn change_file_types(&self) -> Vec<changesets::ChangeType> {
+        self.versioning
+            .release_notes
+            .sections
+            .iter()
+            .flat_map(|(_, sources)| sources.iter().filter_map(ChangeType::to_changeset_type))
+            .collect()
+    }
+
     fn validate(
         package: config::Package,
         git_tags: &[String],
         all_versioned_files: &[VersionedFile],
+        forge: Option<&Forge>,
+        jira: Option<&Jira>,
+        changeset_directory: &RelativePath,
     ) -> Result<Self, Error> {
         if let Name::Custom(package_name) = &package.name {
             debug!("Loading package {package_name}");
         } else {
             debug!("Loading package");
         }
+        let ignore_commits = package
+            .ignore_commits
+            .into_iter()
+            .map(|pattern| {
+                Regex::new(&pattern).map_err(|source| Error::IgnoreCommits { pattern, source })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let (scopes, path_filter) = match (package.paths, package.scopes_and_paths) {
+            (None, _) => (package.scopes, None),
+            (Some(paths), ScopesAndPaths::All) => (
+                package.scopes,
+                Some(PathFilter {
+                    paths,
+                    scopes: None,
+                    mode: ScopesAndPaths::All,
+                }),
+            ),
+            (Some(paths), ScopesAndPaths::Any) => (
+                None,
+                Some(PathFilter {
+                    paths,
+                    scopes: package.scopes,
+                    mode: ScopesAndPaths::Any,
+                }),
+            ),
+        };
         let versioning = knope_versioning::Package::new(
             package.name,
             git_tags,
@@ -72,8 +141,20 @@ impl Package {
             ReleaseNotes {
                 sections: convert_to_versioning(package.extra_changelog_sections),
                 changelog: package.changelog.map(load_changelog).transpose()?,
+                forge: forge.cloned(),
+                jira: jira.cloned(),
+                commit_links: package.commit_links,
+                contributors: package.contributors,
+                templates: Templates {
+                    changelog: package.changelog_template.map(load_template).transpose()?,
+                    release: package.release_template.map(load_template).transpose()?,
+                },
             },
-            package.scopes,
+            CommitFilter {
+                scopes,
+                ignore: ignore_commits,
+            },
+            package.prerelease_channels,
         )?;
         Ok(Self {
             versioning,
@@ -84,6 +165,11 @@ impl Package {
                 GoVersioning::default()
             },
             override_version: None,
+            path_filter,
+            changeset_directory: package.changeset_directory.map_or_else(
+                || changeset_directory.to_relative_path_buf(),
+                |path| path.normalize(),
+            ),
         })
     }
 
@@ -92,32 +178,68 @@ impl Package {
         prepare_release: &PrepareRelease,
         all_tags: &[String],
         versioned_files: Vec<VersionedFile>,
-        changeset: &[changesets::Release],
+        change_files: &ChangeFiles,
+        username_lookup: &mut UsernameLookup,
     ) -> Result<(Vec<VersionedFile>, Vec<Action>), Error> {
         let PrepareRelease {
             prerelease_label,
             ignore_conventional_commits,
+            split_squash_commits,
+            build_metadata,
+            snapshot,
+            since,
+            first_parent,
             ..
         } = prepare_release;
+        let range = CommitRange {
+            since: since.clone(),
+            first_parent: *first_parent,
+            ..CommitRange::default()
+        };
 
         let commit_messages = if *ignore_conventional_commits {
             Vec::new()
         } else {
             conventional_commits::get_conventional_commits_after_last_stable_version(
                 &self.versioning.name,
+                self.path_filter.as_ref(),
+                &self.changeset_directory,
                 all_tags,
+                &range,
             )?
         };
-        let changes = self.versioning.get_changes(changeset, &commit_messages);
+        let mut changes =
+            self.versioning
+                .get_changes(change_files, &commit_messages, *split_squash_commits);
 
         if changes.is_empty() {
             return Ok((versioned_files, Vec::new()));
         }
+        if self.versioning.release_notes.contributors && snapshot.is_none() {
+            let last_release =
+                conventional_commits::last_stable_tag(&self.versioning.name, all_tags);
+            contributors::add_authors(&mut changes, last_release.as_str(), username_lookup)?;
+        }
 
-        let change_config = match self.override_version.take() {
-            Some(version) => ChangeConfig::Force(version),
-            None => ChangeConfig::Calculate {
+        let change_config = match (self.override_version.take(), snapshot) {
+            (Some(version), _) => ChangeConfig::Force(version),
+            (None, Some(snapshot)) => {
+                let last_version = PackageVersions::from_tags(
+                    self.name().as_custom(),
+                    all_tags,
+                    self.versioning.versions.channels().clone(),
+                )
+                .into_latest();
+                let last_tag = ReleaseTag::new(&last_version, self.name());
+                ChangeConfig::Snapshot {
+                    prerelease: snapshot.render(last_tag.as_str(), &range)?,
+                    build_metadata: build_metadata.clone(),
+                    go_versioning: self.go_versioning,
+                }
+            }
+            (None, None) => ChangeConfig::Calculate {
                 prerelease_label: prerelease_label.clone(),
+                build_metadata: build_metadata.clone(),
                 go_versioning: self.go_versioning,
             },
         };
@@ -128,6 +250,11 @@ impl Package {
     }
 }
 
+fn load_template(path: RelativePathBuf) -> Result<Template, Error> {
+    let source = read_to_string(path.to_path(""))?;
+    Ok(Template::parse(path, &source)?)
+}
+
 pub(crate) fn execute_prepare_actions(
     actions: RunType<impl Iterator<Item = Action>>,
     stage_to_git: bool,
@@ -147,7 +274,9 @@ pub(crate) fn execute_prepare_actions(
                     RunType::Real(()) => WriteType::Real(content),
                 };
                 fs::write(write_type, &path.to_path(""))?;
-                paths_to_stage.push(path);
+                if !paths_to_stage.contains(&path) {
+                    paths_to_stage.push(path);
+                }
             }
             Action::RemoveFile { path } => {
                 // Ignore errors since we remove changesets per-package
@@ -211,13 +340,21 @@ impl Package {
                 ReleaseNotes {
                     sections: knope_versioning::release_notes::Sections::default(),
                     changelog: None,
+                    forge: None,
+                    jira: None,
+                    commit_links: None,
+                    contributors: false,
+                    templates: Templates::default(),
                 },
-                None,
+                CommitFilter::default(),
+                knope_versioning::semver::Channels::default(),
             )
             .unwrap(),
             override_version: None,
             assets: None,
             go_versioning: GoVersioning::default(),
+            path_filter: None,
+            changeset_directory: RelativePathBuf::from(knope_versioning::changes::CHANGESET_DIR),
         }
     }
 }
@@ -233,7 +370,7 @@ impl Display for Package {
 pub(crate) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
-    Time(#[from] TimeError),
+    Template(#[from] TemplateError),
     #[error("Could not serialize generated TOML")]
     #[diagnostic(
         code(releases::package::could_not_serialize_toml),
@@ -268,4 +405,21 @@ pub(crate) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
     New(#[from] Box<PackageNewError>),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Snapshot(#[from] snapshot::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Contributors(#[from] contributors::Error),
+    #[error("Invalid regular expression in ignore_commits: {pattern}")]
+    #[diagnostic(
+        code(package::ignore_commits),
+        help("{source}"),
+        url("https://knope.tech/reference/config-file/packages/#ignore_commits")
+    )]
+    IgnoreCommits {
+        pattern: String,
+        #[source]
+        source: regex::Error,
+    },
 }
@@ -1,12 +1,16 @@
 use knope_versioning::{
     package::{Bump, BumpError},
-    semver::{PreReleaseNotFound, Rule},
+    semver::PreReleaseNotFound,
     GoVersioning, VersionedFile,
 };
 use miette::Diagnostic;
 
 use crate::{
-    fs, integrations::git, state::State, step::releases::package::execute_prepare_actions, RunType,
+    fs,
+    integrations::git,
+    state::State,
+    step::{releases::package::execute_prepare_actions, BumpVersion},
+    RunType,
 };
 
 /// The implementation of [`crate::step::Step::BumpVersion`].
@@ -14,7 +18,10 @@ use crate::{
 /// Bumps the version of every configured package using `rule`.
 pub(crate) fn bump_version_and_update_state(
     state: RunType<State>,
-    rule: &Rule,
+    BumpVersion {
+        rule,
+        build_metadata,
+    }: &BumpVersion,
 ) -> Result<RunType<State>, Error> {
     let (run_type, mut state) = state.take();
 
@@ -22,7 +29,13 @@ pub(crate) fn bump_version_and_update_state(
         let (bump, go_versioning) = if let Some(version) = package.override_version.clone() {
             (Bump::Manual(version), GoVersioning::BumpMajor)
         } else {
-            (Bump::Rule(rule.clone()), package.go_versioning)
+            (
+                Bump::Rule {
+                    rule: rule.clone(),
+                    build_metadata: build_metadata.clone(),
+                },
+                package.go_versioning,
+            )
         };
         state.all_versioned_files =
             package
@@ -0,0 +1,71 @@
+use std::str::FromStr;
+
+use knope_versioning::semver::Prerelease;
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use time::{macros::format_description, OffsetDateTime};
+
+use crate::integrations::git::{self, get_commit_messages_after_tag, short_head_sha, CommitRange};
+
+/// A template for the pre-release component of snapshot versions, like `nightly.{date}.{sha}`.
+///
+/// Supported placeholders are:
+/// - `{date}`: the current UTC date as `YYYYMMDD`
+/// - `{sha}`: the abbreviated ID of the `HEAD` commit
+/// - `{commits}`: the number of commits since `last_tag` (or the start of the commit range)
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub(crate) struct Snapshot(String);
+
+impl Snapshot {
+    /// Render the template into a pre-release, only looking up the placeholders that are used.
+    pub(crate) fn render(&self, last_tag: &str, range: &CommitRange) -> Result<Prerelease, Error> {
+        let mut rendered = self.0.clone();
+        if rendered.contains("{date}") {
+            let format = format_description!("[year][month][day]");
+            let date = OffsetDateTime::now_utc().date().format(&format)?;
+            rendered = rendered.replace("{date}", &date);
+        }
+        if rendered.contains("{sha}") {
+            let mut sha = short_head_sha()?;
+            // Numeric identifiers can't have leading zeros, so make sure this one isn't numeric
+            if sha.starts_with('0') && sha.chars().all(|c| c.is_ascii_digit()) {
+                sha.insert(0, 'g');
+            }
+            rendered = rendered.replace("{sha}", &sha);
+        }
+        if rendered.contains("{commits}") {
+            let commits = get_commit_messages_after_tag(last_tag, range)?.len();
+            rendered = rendered.replace("{commits}", &commits.to_string());
+        }
+        Prerelease::from_str(&rendered).map_err(|source| Error::Invalid {
+            template: self.0.clone(),
+            rendered,
+            source,
+        })
+    }
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("The snapshot template {template} produced an invalid pre-release: {rendered}")]
+    #[diagnostic(
+        code(releases::invalid_snapshot),
+        help(
+            "Snapshot templates must produce a valid pre-release, like `nightly.{{date}}.{{sha}}`. \
+            The supported placeholders are `{{date}}`, `{{sha}}`, and `{{commits}}`."
+        ),
+        url("https://knope.tech/reference/config-file/steps/prepare-release/#snapshot")
+    )]
+    Invalid {
+        template: String,
+        rendered: String,
+        #[source]
+        source: knope_versioning::semver::Error,
+    },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error("Could not format the current date: {0}")]
+    Time(#[from] time::error::Format),
+}
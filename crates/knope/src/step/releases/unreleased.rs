@@ -0,0 +1,78 @@
+use miette::Diagnostic;
+use tracing::debug;
+
+use super::{
+    change_files, contributors,
+    contributors::UsernameLookup,
+    conventional_commits::{get_conventional_commits_after_last_stable_version, last_stable_tag},
+    package::execute_prepare_actions,
+};
+use crate::{
+    integrations::git::{self, CommitRange},
+    state::{RunType, State},
+};
+
+/// Write the notes for everything that hasn't been released yet to the "Unreleased" section of
+/// each package's changelog, keeping anything that was written there by hand.
+pub(crate) fn update_unreleased(state: RunType<State>) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    if state.packages.is_empty() {
+        return Err(super::package::Error::NoDefinedPackages.into());
+    }
+
+    let change_files = change_files::load(&state.changeset_directory, &state.packages)?;
+    let mut username_lookup = UsernameLookup::new(
+        run_type,
+        (state.github_config.as_ref(), &mut state.github),
+        (state.gitea_config.as_ref(), &mut state.gitea),
+    );
+    let mut actions = Vec::new();
+    for package in &mut state.packages {
+        let commits = get_conventional_commits_after_last_stable_version(
+            package.name(),
+            package.path_filter.as_ref(),
+            &package.changeset_directory,
+            &state.all_git_tags,
+            &CommitRange::default(),
+        )?;
+        let mut changes = package
+            .versioning
+            .get_changes(&change_files, &commits, false);
+        if package.versioning.release_notes.contributors {
+            let last_release = last_stable_tag(package.name(), &state.all_git_tags);
+            contributors::add_authors(&mut changes, last_release.as_str(), &mut username_lookup)?;
+        }
+        let name = package.name().clone();
+        if let Some(action) = package
+            .versioning
+            .release_notes
+            .update_unreleased(&changes, &name)?
+        {
+            actions.push(action);
+        } else {
+            debug!("Package {name} has no changelog to update");
+        }
+    }
+
+    execute_prepare_actions(run_type.of(actions.into_iter()), false)?;
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Package(#[from] super::package::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ChangeFiles(#[from] change_files::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Contributors(#[from] contributors::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ReleaseNotes(#[from] knope_versioning::release_notes::Error),
+}
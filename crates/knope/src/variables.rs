@@ -135,8 +135,10 @@ pub(crate) enum Error {
 #[allow(clippy::indexing_slicing)]
 mod test_replace_variables {
     use knope_versioning::{
+        changes::conventional_commit::CommitFilter,
         package::Name,
-        release_notes::{Changelog, ReleaseNotes, Sections},
+        release_notes::{Changelog, ReleaseNotes, Sections, Templates},
+        semver::Channels,
         Action, VersionedFile, VersionedFileConfig,
     };
     use pretty_assertions::assert_eq;
@@ -164,8 +166,14 @@ mod test_replace_variables {
                 ReleaseNotes {
                     sections: Sections::default(),
                     changelog: Some(changelog),
+                    forge: None,
+                    jira: None,
+                    commit_links: None,
+                    contributors: false,
+                    templates: Templates::default(),
                 },
-                None,
+                CommitFilter::default(),
+                Channels::default(),
             )
             .unwrap(),
             ..Package::default()
@@ -224,6 +232,7 @@ mod test_replace_variables {
             all_git_tags: Vec::new(),
             all_versioned_files: Vec::new(),
             pending_actions: Vec::new(),
+            changeset_directory: relative_path::RelativePathBuf::from(".changeset"),
         };
 
         let result = replace_variables(
@@ -1,11 +1,18 @@
 use std::fmt::Debug;
 
 use itertools::Itertools;
+use knope_versioning::semver::BuildMetadata;
 use miette::Diagnostic;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::{state::RunType, step, step::Step, State};
+use crate::{
+    integrations::git::Since,
+    state::RunType,
+    step,
+    step::{releases::change_files, CreateChangeFile, Step},
+    State,
+};
 
 /// A workflow is basically the state machine to run for a single execution of knope.
 #[derive(Debug, Deserialize, Serialize)]
@@ -25,6 +32,27 @@ impl Workflow {
             step.set_prerelease_label(prerelease_label);
         }
     }
+
+    /// Set `since` for any steps that are `PrepareRelease` steps.
+    pub(crate) fn set_since(&mut self, since: &Since) {
+        for step in &mut self.steps {
+            step.set_since(since);
+        }
+    }
+
+    /// Override the inputs of any `CreateChangeFile` steps.
+    pub(crate) fn set_change_file_inputs(&mut self, inputs: &CreateChangeFile) {
+        for step in &mut self.steps {
+            step.set_change_file_inputs(inputs);
+        }
+    }
+
+    /// Set `build_metadata` for any steps that are `PrepareRelease` or `BumpVersion` steps.
+    pub(crate) fn set_build_metadata(&mut self, build_metadata: &BuildMetadata) {
+        for step in &mut self.steps {
+            step.set_build_metadata(build_metadata);
+        }
+    }
 }
 
 /// A collection of errors from running with the `--validate` option.
@@ -32,7 +60,17 @@ impl Workflow {
 #[error("There are problems with the defined workflows")]
 pub struct ValidationErrorCollection {
     #[related]
-    errors: Vec<Error>,
+    errors: Vec<ValidationError>,
+}
+
+#[derive(Debug, thiserror::Error, Diagnostic)]
+enum ValidationError {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Workflow(#[from] Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ChangeFiles(#[from] change_files::Error),
 }
 
 /// An error from running or validating a single workflow.
@@ -44,6 +82,12 @@ pub struct Error {
     inner: Box<[step::Error; 1]>,
 }
 
+impl Error {
+    fn is_from_change_files(&self) -> bool {
+        self.inner.iter().any(step::Error::is_from_change_files)
+    }
+}
+
 /// Run a series of [`Step`], each of which updates `state`.
 pub(crate) fn run(workflow: Workflow, mut state: RunType<State>) -> Result<(), Error> {
     for step in workflow.steps {
@@ -65,9 +109,18 @@ pub(crate) fn validate(
     workflows: Vec<Workflow>,
     state: State,
 ) -> Result<(), ValidationErrorCollection> {
-    let errors = workflows
+    // Change files are checked once here, instead of by every workflow that uses them
+    let change_files = change_files::load(&state.changeset_directory, &state.packages).err();
+    let errors = change_files
         .into_iter()
-        .filter_map(|workflow| run(workflow, RunType::DryRun(state.clone())).err())
+        .map(ValidationError::from)
+        .chain(
+            workflows
+                .into_iter()
+                .filter_map(|workflow| run(workflow, RunType::DryRun(state.clone())).err())
+                .filter(|err| !err.is_from_change_files())
+                .map(ValidationError::from),
+        )
         .collect_vec();
 
     if errors.is_empty() {
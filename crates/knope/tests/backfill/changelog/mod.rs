@@ -0,0 +1,24 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Add an entry for each existing release tag, including pre-releases
+#[test]
+fn changelog() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: First feature"),
+            Tag("v1.0.0"),
+            Commit("fix: A fix"),
+            Tag("v1.0.1"),
+            Commit("feat: Another feature"),
+            Tag("v1.1.0-rc.0"),
+            Commit("fix: A fix for the feature"),
+            Tag("v1.1.0"),
+            Commit("chore: Nothing to release"),
+            Tag("v1.1.1"),
+            Commit("fix: Not released yet"),
+        ])
+        .run("backfill");
+}
@@ -0,0 +1,19 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Versions which are already in the changelog are left alone
+#[test]
+fn existing_entries() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: First feature"),
+            Tag("v1.0.0"),
+            Commit("fix: A fix"),
+            Tag("v1.0.1"),
+            Commit("feat: Another feature"),
+            Tag("v1.1.0"),
+        ])
+        .run("backfill");
+}
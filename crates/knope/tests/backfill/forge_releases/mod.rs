@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Releases are only created if they're missing, which requires talking to GitHub
+#[test]
+fn forge_releases() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: First feature"),
+            Tag("v1.0.0"),
+            Commit("fix: A fix"),
+            Tag("v1.0.1"),
+        ])
+        .run("backfill --dry-run"); // Cannot check for existing releases without GitHub
+}
@@ -0,0 +1,4 @@
+mod changelog;
+mod existing_entries;
+mod forge_releases;
+mod squash_commits_and_contributors;
@@ -0,0 +1,19 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Backfilled entries split squash merges and thank contributors, like `PrepareRelease` does
+#[test]
+fn squash_commits_and_contributors() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: First feature"),
+            Tag("v1.0.0"),
+            Commit(
+                "Squashed (#12)\n\n* feat: Add X\n\n* fix: Fix Y\n\nCo-authored-by: Bob <bob@example.com>",
+            ),
+            Tag("v1.1.0"),
+        ])
+        .run("backfill");
+}
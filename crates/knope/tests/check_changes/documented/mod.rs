@@ -0,0 +1,26 @@
+use std::fs::{create_dir_all, write};
+
+use crate::helpers::{add_all, commit, commit_file, create_branch, TestCase};
+
+/// Each changed package is documented by either a conventional commit or a new change file
+#[test]
+fn documented() {
+    let test = TestCase::new(file!());
+    let temp_dir = test.arrange();
+    let temp_path = temp_dir.path();
+
+    commit(temp_path, "Initial commit");
+    create_branch(temp_path, "feature");
+    commit_file(temp_path, "first/lib.rs", "feat: A documented feature");
+    commit_file(temp_path, "second/lib.rs", "Change second");
+    create_dir_all(temp_path.join(".changeset")).unwrap();
+    write(
+        temp_path.join(".changeset/a_fix_for_second.md"),
+        "---\nsecond: patch\n---\n\n# A fix for second\n",
+    )
+    .unwrap();
+    add_all(temp_path);
+    commit(temp_path, "Document second");
+
+    test.assert(test.act(temp_dir, "check-changes"));
+}
@@ -0,0 +1,25 @@
+use std::fs::{create_dir_all, write};
+
+use crate::helpers::{add_all, commit, commit_file, create_branch, TestCase};
+
+/// Change files which already existed on the base branch don't document new changes, and
+/// packages without changes don't need documenting
+#[test]
+fn missing() {
+    let test = TestCase::new(file!());
+    let temp_dir = test.arrange();
+    let temp_path = temp_dir.path();
+
+    create_dir_all(temp_path.join(".changeset")).unwrap();
+    write(
+        temp_path.join(".changeset/an_old_change.md"),
+        "---\nfirst: patch\n---\n\n# An old change\n",
+    )
+    .unwrap();
+    add_all(temp_path);
+    commit(temp_path, "Initial commit");
+    create_branch(temp_path, "feature");
+    commit_file(temp_path, "first/lib.rs", "Change first");
+
+    test.assert(test.act(temp_dir, "check-changes"));
+}
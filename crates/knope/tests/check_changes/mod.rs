@@ -0,0 +1,2 @@
+mod documented;
+mod missing;
@@ -0,0 +1,20 @@
+use crate::helpers::{commit, create_branch, TestCase};
+
+/// Each commit with problems is reported, with each problem labeled
+#[test]
+fn invalid() {
+    let test = TestCase::new(file!());
+    let temp_dir = test.arrange();
+    let temp_path = temp_dir.path();
+
+    commit(temp_path, "Initial commit");
+    create_branch(temp_path, "feature");
+    commit(temp_path, "Add a feature");
+    commit(temp_path, "feat(knope): A valid feature");
+    commit(
+        temp_path,
+        "fix(config): A fix with an unknown scope\n\nMystery-Footer: What does this do?",
+    );
+
+    test.assert(test.act(temp_dir, "check-commits"));
+}
@@ -0,0 +1,22 @@
+use crate::helpers::{commit, create_branch, merge_branch, switch_branch, TestCase};
+
+/// Commits on `main` aren't checked, and merge commits and ignored commits are allowed
+#[test]
+fn valid() {
+    let test = TestCase::new(file!());
+    let temp_dir = test.arrange();
+    let temp_path = temp_dir.path();
+
+    commit(temp_path, "Not a conventional commit");
+    create_branch(temp_path, "feature");
+    commit(temp_path, "feat(knope): A feature\n\nCloses #12");
+    commit(temp_path, "fix: A fix\n\nChangelog-Note: Something to note");
+    commit(temp_path, "chore(deps): Update everything");
+    switch_branch(temp_path, "main");
+    create_branch(temp_path, "other");
+    commit(temp_path, "docs(versioning): Document something");
+    switch_branch(temp_path, "feature");
+    merge_branch(temp_path, "other");
+
+    test.assert(test.act(temp_dir, "check-commits"));
+}
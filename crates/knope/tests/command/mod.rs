@@ -1,3 +1,4 @@
+mod prerelease_channels;
 mod replace_version;
 #[cfg(not(windows))]
 mod shell;
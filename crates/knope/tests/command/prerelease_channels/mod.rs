@@ -0,0 +1,18 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// The latest version is the pre-release in the most stable channel, not the alphabetically last.
+#[test]
+fn prerelease_channels() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+            Tag("v1.1.0-preview.0"),
+            Tag("v1.1.0-next.0"),
+        ])
+        .run("version");
+}
@@ -0,0 +1,19 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Changes from commits since the last release become change files, unless a commit has a change
+/// which a change file can't express
+#[test]
+fn convert() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: A feature that needs better wording"),
+            Commit("fix: A bug\n\nChangelog-Note: A note that stays in the commit"),
+            Commit("docs: Not a change"),
+        ])
+        .run("convert");
+}
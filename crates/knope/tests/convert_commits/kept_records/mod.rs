@@ -0,0 +1,16 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Converted commits are only forgotten in directories whose change files were released
+#[test]
+fn kept_records() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first/v1.0.0"),
+            Tag("second/v1.0.0"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,3 @@
+mod convert;
+mod kept_records;
+mod then_release;
@@ -0,0 +1,18 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Converted commits are only counted once, through their change files
+#[test]
+fn then_release() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: A feature that needs better wording"),
+            Commit("fix: A bug\n\nChangelog-Note: A note that stays in the commit"),
+            Commit("docs: Not a change"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Every input can be passed on the command line, so nothing is prompted for
+#[test]
+fn cli_args() {
+    TestCase::new(file!()).run("document-change --package first --change-type poem --summary Typo");
+}
@@ -0,0 +1,7 @@
+# Keeps the summary header (and instructions) from the template, adding a body after the header
+{
+    head -n 1 "$1"
+    printf '\nA body with code:\n\n```rust\nfn main() {}\n```\n'
+    tail -n +2 "$1"
+} > "$1.new"
+mv "$1.new" "$1"
@@ -0,0 +1,9 @@
+use crate::helpers::TestCase;
+
+/// The summary and body are written in `$EDITOR`, starting from the configured summary
+#[test]
+fn editor() {
+    TestCase::new(file!())
+        .env("EDITOR", "sh editor.sh")
+        .run("document-change");
+}
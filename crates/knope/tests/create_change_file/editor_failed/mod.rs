@@ -0,0 +1,9 @@
+use crate::helpers::TestCase;
+
+/// If the editor fails, no change file is created
+#[test]
+fn editor_failed() {
+    TestCase::new(file!())
+        .env("EDITOR", "false")
+        .run("document-change --editor");
+}
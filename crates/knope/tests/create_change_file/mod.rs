@@ -0,0 +1,7 @@
+mod cli_args;
+mod editor;
+mod editor_failed;
+mod not_interactive;
+mod package_directory;
+mod step_config;
+mod unknown_change_type;
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Without a terminal, missing inputs are an error instead of a prompt
+#[test]
+fn not_interactive() {
+    TestCase::new(file!()).run("document-change --summary Typo");
+}
@@ -0,0 +1,8 @@
+use crate::helpers::TestCase;
+
+/// A change file for a package with its own `changeset_directory` is written there
+#[test]
+fn package_directory() {
+    TestCase::new(file!())
+        .run("document-change --package first --change-type minor --summary Nearby");
+}
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Inputs set in the step don't need to be prompted for, and can use variables
+#[test]
+fn step_config() {
+    TestCase::new(file!()).run("document-change");
+}
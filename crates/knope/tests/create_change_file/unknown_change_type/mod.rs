@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Change types must come from the package's changelog sections
+#[test]
+fn unknown_change_type() {
+    TestCase::new(file!()).run("document-change --package second --change-type poem --summary Ode");
+}
@@ -77,6 +77,30 @@ pub fn commit(path: &Path, message: &str) {
     );
 }
 
+/// Append a line to `file` (creating it if needed) and commit only that file with `message`.
+pub fn commit_file(path: &Path, file: &str, message: &str) {
+    let file_path = path.join(file);
+    if let Some(parent) = file_path.parent() {
+        std::fs::create_dir_all(parent).unwrap();
+    }
+    let mut contents = std::fs::read_to_string(&file_path).unwrap_or_default();
+    contents.push_str(message);
+    contents.push('\n');
+    std::fs::write(&file_path, contents).unwrap();
+    let output = Command::new("git")
+        .arg("add")
+        .arg(file)
+        .current_dir(path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    commit(path, message);
+}
+
 /// Create a tag with `label` in the Git repo which exists in `path`.
 pub fn tag(path: &Path, label: &str) {
     let output = Command::new("git")
@@ -9,7 +9,9 @@ use snapbox::{
 };
 use tempfile::TempDir;
 
-use crate::helpers::{add_remote, assert, commit, copy_dir_contents, get_tags, init, tag};
+use crate::helpers::{
+    add_remote, assert, commit, commit_file, copy_dir_contents, get_tags, init, tag,
+};
 
 pub struct TestCase {
     file_name: &'static str,
@@ -71,6 +73,9 @@ impl TestCase {
                 GitCommand::Commit(message) => {
                     commit(path, message);
                 }
+                GitCommand::CommitFile(file, message) => {
+                    commit_file(path, file, message);
+                }
                 GitCommand::Tag(name) => {
                     tag(path, name);
                 }
@@ -212,5 +217,7 @@ pub struct Asserts {
 #[derive(Clone, Copy, Debug)]
 pub enum GitCommand {
     Commit(&'static str),
+    /// Change the file at the first path and commit only that file with the second message.
+    CommitFile(&'static str, &'static str),
     Tag(&'static str),
 }
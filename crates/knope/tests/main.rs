@@ -1,6 +1,11 @@
 #![allow(clippy::unwrap_used)]
+mod backfill;
 mod bump_version;
+mod check_changes;
+mod check_commits;
 mod command;
+mod convert_commits;
+mod create_change_file;
 mod default_workflows;
 mod generate;
 mod git_release;
@@ -10,5 +15,6 @@ mod helpers;
 mod multi_forge_release;
 mod no_config;
 mod prepare_release;
+mod update_unreleased;
 mod upgrade;
 mod validate;
@@ -0,0 +1,25 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+const TEST_CASE: TestCase = TestCase::new(file!()).git(&[
+    Commit("Initial commit"),
+    Tag("v1.0.0+build.1"),
+    Commit("feat: A new feature"),
+]);
+
+/// Attach the configured `build_metadata` to the new version, tags with build metadata are still
+/// used to find the previous version.
+#[test]
+fn from_config() {
+    TEST_CASE.run("release");
+}
+
+/// Override the configured `build_metadata` with the `--build-metadata` argument.
+#[test]
+fn with_option() {
+    TEST_CASE
+        .env("KNOPE_BUILD_METADATA", "ignored")
+        .run("release --build-metadata=abc1234");
+}
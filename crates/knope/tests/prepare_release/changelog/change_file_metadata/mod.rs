@@ -0,0 +1,20 @@
+use crate::helpers::{
+    GitCommand::{Commit, CommitFile, Tag},
+    TestCase,
+};
+
+/// Link the issues, pull request, and authors from change file metadata like those of commits
+#[test]
+fn change_file_metadata() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat: A new feature (#12)"),
+            CommitFile(
+                ".changeset/a_documented_change.md",
+                "---\ndefault: patch\nissues: [34, PRJ-5]\npr: 56\nauthors: [alice, \"Bob <bob@example.com>\"]\n---\n\n# A documented change",
+            ),
+        ])
+        .run("release");
+}
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Link each change to its commit, author, and pull request using the GitHub config
+#[test]
+fn commit_links() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat: A new feature (#12)"),
+            Commit("fix: A bug fix"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+#[test]
+fn bump_from_commit_types() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("docs: Document everything"),
+            Commit("perf: Go faster"),
+            Commit("chore: Not included"),
+        ])
+        .run("release");
+}
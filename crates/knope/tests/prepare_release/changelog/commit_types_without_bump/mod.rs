@@ -0,0 +1,16 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Changes which don't bump the version shouldn't cause a release on their own
+#[test]
+fn no_release() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("docs: Document everything"),
+        ])
+        .run("release");
+}
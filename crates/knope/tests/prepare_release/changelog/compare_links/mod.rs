@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Keep a Changelog style reference links at the end of the changelog compare each release to the
+/// previous one
+#[test]
+fn compare_links() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat: A new feature"),
+        ])
+        .run("release");
+}
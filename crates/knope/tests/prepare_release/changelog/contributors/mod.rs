@@ -0,0 +1,25 @@
+use crate::helpers::{
+    GitCommand::{Commit, CommitFile, Tag},
+    TestCase,
+};
+
+/// Thank commit authors, co-authors, and the authors of change files (or those they list)
+#[test]
+fn contributors() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat: A new feature\n\nCo-authored-by: Alice <1234+alice@users.noreply.github.com>"),
+            Commit("fix: A bug fix\n\nCo-authored-by: Bob <bob@example.com>"),
+            CommitFile(
+                ".changeset/a_documented_change.md",
+                "---\ndefault: patch\n---\n\n# A documented change",
+            ),
+            CommitFile(
+                ".changeset/a_change_with_authors.md",
+                "---\ndefault: patch\nauthors: [carol]\n---\n\n# A change with authors",
+            ),
+        ])
+        .run("release");
+}
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Link issue references from commits and change files to GitHub and Jira
+#[test]
+fn issue_links() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat: A new feature\n\nCloses #12, PRJ-34"),
+            Commit("fix: A bug fix\n\nFixes OTHER-1"),
+        ])
+        .run("release");
+}
@@ -1,4 +1,13 @@
+mod change_file_metadata;
+mod commit_links;
+mod commit_types;
+mod commit_types_without_bump;
+mod compare_links;
+mod contributors;
 mod create_missing;
 mod extra_changelog_sections;
 mod header_level_detection;
+mod issue_links;
 mod override_default_sections;
+mod templates;
+mod unreleased;
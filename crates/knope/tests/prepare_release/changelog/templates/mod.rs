@@ -0,0 +1,21 @@
+use crate::helpers::{
+    GitCommand::{Commit, CommitFile, Tag},
+    TestCase,
+};
+
+/// Lay out the changelog and the GitHub release with separate templates
+#[test]
+fn templates() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat: A new feature (#12)"),
+            Commit("fix: A bug\n\nCloses #3"),
+            CommitFile(
+                ".changeset/a_documented_change.md",
+                "---\ndefault: minor\nauthors: [alice, \"Bob <bob@example.com>\"]\n---\n\n# A documented change\n\nWith some details\nover two lines",
+            ),
+        ])
+        .run("release --dry-run");
+}
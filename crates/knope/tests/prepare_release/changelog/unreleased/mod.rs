@@ -0,0 +1,18 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Whatever was written by hand in the "Unreleased" section is merged into the new release, and
+/// the section is left empty
+#[test]
+fn unreleased() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat: A new feature"),
+            Commit("fix: A bug"),
+        ])
+        .run("release --dry-run");
+}
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+#[test]
+fn skip_override_and_ignore() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("fix: Internal only\n\nChangelog: skip"),
+            Commit("feat: asdf\n\nChangelog-Description: A useful description"),
+            Commit("feat(deps)!: Upgrade everything"),
+        ])
+        .run("release");
+}
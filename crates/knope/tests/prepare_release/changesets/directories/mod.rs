@@ -0,0 +1,16 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Change files are read from the project-wide `changeset_directory` and from each package's own
+#[test]
+fn directories() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first/v1.2.3"),
+            Tag("second/v0.4.6"),
+        ])
+        .run("release");
+}
@@ -1,3 +1,4 @@
+mod directories;
 mod happy;
 mod invalid_change_file;
 mod prerelease;
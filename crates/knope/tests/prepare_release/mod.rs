@@ -1,7 +1,9 @@
 mod allow_empty;
 mod branching_history;
+mod build_metadata;
 mod cargo_workspace;
 mod changelog;
+mod changelog_trailers;
 mod changesets;
 mod enable_prerelease;
 mod go_modules;
@@ -16,12 +18,17 @@ mod no_versioned_files;
 mod override_prerelease_label;
 mod override_version;
 mod override_version_multiple_packages;
+mod package_paths;
 mod package_selection;
 mod prerelease_after_release;
 mod pubspec_yaml;
 mod pyproject_toml;
 mod release_after_prerelease;
+mod revert_commits;
 mod scopes;
 mod second_prerelease;
+mod since;
+mod snapshot;
+mod split_squash_commits;
 mod unknown_versioned_file_format;
 mod verbose;
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, CommitFile},
+    TestCase,
+};
+
+/// Commits must touch a package's `paths` _and_ match its `scopes`
+#[test]
+fn commits_must_match_paths_and_scopes() {
+    TestCase::new(file!())
+        .git(&[
+            CommitFile("first/lib.rs", "fix: Fix for first"),
+            CommitFile("first/main.rs", "feat(second): Wrong scope for first"),
+            CommitFile("second/main.py", "feat: Feature for second"),
+            Commit("feat!: Doesn't touch any files"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, CommitFile},
+    TestCase,
+};
+
+/// Commits must touch a package's `paths` _or_ match its `scopes`
+#[test]
+fn commits_can_match_paths_or_scopes() {
+    TestCase::new(file!())
+        .git(&[
+            CommitFile("first/lib.rs", "fix(second): Touches first"),
+            Commit("feat(first): Scoped to first"),
+            CommitFile("second/main.py", "fix: Fix for second"),
+            Commit("feat!: Doesn't touch any files"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,2 @@
+mod all;
+mod any;
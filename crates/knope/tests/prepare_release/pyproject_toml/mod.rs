@@ -1,3 +1,4 @@
 mod mixed;
+mod pep440;
 mod pep621;
 mod poetry;
@@ -0,0 +1,18 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Versions in `pyproject.toml` are PEP 440, tags are still Semantic Versions.
+#[test]
+fn prerelease() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+            Tag("v1.1.0-preview.0"),
+            Commit("fix: A bug"),
+        ])
+        .run("release --prerelease-label=preview");
+}
@@ -0,0 +1,20 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Reverted commits, and the commits reverting them, shouldn't be released
+#[test]
+fn reverted_changes_are_dropped() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat!: Break everything"),
+            Commit("feat: Add X"),
+            Commit("fix: Fix Y"),
+            Commit("revert: feat: Add X"),
+            Commit("Revert \"feat!: Break everything\""),
+        ])
+        .run("release");
+}
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+#[test]
+fn cli_flag() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat: Released by another tool"),
+            Tag("migrated"),
+            Commit("fix: A new fix"),
+        ])
+        .run("release --since migrated");
+}
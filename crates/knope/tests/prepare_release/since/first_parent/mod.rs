@@ -0,0 +1,19 @@
+use crate::helpers::{commit, create_branch, merge_branch, switch_branch, tag, TestCase};
+
+/// Skip commits which only appear on merged branches
+#[test]
+fn first_parent() {
+    let test = TestCase::new(file!());
+    let temp_dir = test.arrange();
+    let temp_path = temp_dir.path();
+
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.0.0");
+    create_branch(temp_path, "feature");
+    commit(temp_path, "feat: Only on the feature branch");
+    switch_branch(temp_path, "main");
+    commit(temp_path, "fix: A fix on main");
+    merge_branch(temp_path, "feature");
+
+    test.assert(test.act(temp_dir, "release"));
+}
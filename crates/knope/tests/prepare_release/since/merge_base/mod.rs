@@ -0,0 +1,20 @@
+use crate::helpers::{commit, create_branch, switch_branch, tag, TestCase};
+
+/// Only use commits on a backport branch, not those from `main` before it branched
+#[test]
+fn merge_base() {
+    let test = TestCase::new(file!());
+    let temp_dir = test.arrange();
+    let temp_path = temp_dir.path();
+
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: Not released yet");
+    create_branch(temp_path, "backport");
+    commit(temp_path, "fix: A backported fix");
+    switch_branch(temp_path, "main");
+    commit(temp_path, "feat: Another new feature");
+    switch_branch(temp_path, "backport");
+
+    test.assert(test.act(temp_dir, "release"));
+}
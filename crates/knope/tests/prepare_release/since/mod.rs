@@ -0,0 +1,5 @@
+mod cli_flag;
+mod first_parent;
+mod merge_base;
+mod revision;
+mod unknown_revision;
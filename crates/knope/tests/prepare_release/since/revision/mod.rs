@@ -0,0 +1,18 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Start from a tag which isn't a version, like one left by a migration from another tool
+#[test]
+fn revision() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat: Released by another tool"),
+            Tag("migrated"),
+            Commit("fix: A new fix"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Snapshots update versioned files, but leave the changelog and change files alone.
+#[test]
+fn snapshot() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.2.3"),
+            Commit("fix: A bug"),
+            Commit("feat: A feature"),
+        ])
+        .run("nightly");
+}
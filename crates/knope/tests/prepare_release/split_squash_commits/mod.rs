@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+#[test]
+fn each_squashed_commit_is_a_change() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit(
+                "Big pull request (#12)\n\n* feat: Add X\n\n* fix: Fix Y\n\nChangelog-Note: Y was very broken\n\n* chore: Clean up",
+            ),
+        ])
+        .run("release");
+}
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// The pending changes in the "Unreleased" section are replaced, and those written by hand are kept
+#[test]
+fn update() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat: A new feature"),
+            Commit("fix: A bug"),
+        ])
+        .run("update");
+}
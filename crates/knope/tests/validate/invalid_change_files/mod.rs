@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Problems in change files are reported once, even though the `release` workflow reads them too
+#[test]
+fn invalid_change_files() {
+    TestCase::new(file!()).run("--validate");
+}
@@ -1,3 +1,4 @@
+mod invalid_change_files;
 mod kitchen_sink;
 mod multiple_package_formats;
 mod unsupported_dependency;